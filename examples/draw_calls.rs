@@ -0,0 +1,93 @@
+//! Benchmark of the cost of a draw call: every quad alternates between two textures, so none
+//! of them batch together. Prints the average frame time every second.
+//!
+//! `cargo run --release --example draw_calls`, with `DRAW_CALLS` to change the number of
+//! quads (20000 by default). Frame times under the display refresh interval are hidden by
+//! vsync, so raise it until they are above that to compare two builds.
+
+use miniquad::TextureId;
+use porcelain::{
+    color::Color,
+    conf::WindowConfig,
+    render::{DrawContext, EventListener, TextureArea},
+    start,
+    texture::TextureContext,
+};
+
+struct AppState {
+    draw_calls: usize,
+    textures: Option<[TextureId; 2]>,
+    frames: u32,
+    elapsed: f64,
+}
+
+impl EventListener for AppState {
+    fn update(&mut self, texture_context: &TextureContext, dt: f64) {
+        if self.textures.is_none() {
+            self.textures = Some([
+                texture_context.register_texture_rgba8(1, 1, &[255, 160, 64, 255]),
+                texture_context.register_texture_rgba8(1, 1, &[64, 160, 255, 255]),
+            ]);
+        }
+
+        self.frames += 1;
+        self.elapsed += dt;
+        if self.elapsed >= 1. {
+            println!(
+                "{} draw calls: {:.2} ms per frame",
+                self.draw_calls,
+                self.elapsed * 1000. / self.frames as f64
+            );
+            self.frames = 0;
+            self.elapsed = 0.;
+        }
+    }
+
+    fn draw(&self, draw_context: &mut DrawContext) {
+        let Some(textures) = self.textures else {
+            return;
+        };
+
+        let (width, height) = draw_context.screen_size();
+        let columns = (width / 8.).max(1.) as usize;
+        let rows = (height / 8.).max(1.) as usize;
+        for i in 0..self.draw_calls {
+            let cell = i % (columns * rows);
+            let area = TextureArea {
+                texture: textures[i % 2],
+                location: glam::Vec2::ZERO,
+                size: glam::Vec2::ONE,
+            };
+            draw_context.draw_texture(
+                (cell % columns) as f32 * 8.,
+                (cell / columns) as f32 * 8.,
+                6.,
+                6.,
+                area,
+                Color::from_rgba8(255, 255, 255, 255),
+            );
+        }
+    }
+}
+
+fn main() {
+    let draw_calls = std::env::var("DRAW_CALLS")
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(20000);
+
+    let window_config = WindowConfig {
+        window_title: "Draw calls".to_owned(),
+        ..Default::default()
+    };
+
+    start(
+        window_config,
+        AppState {
+            draw_calls,
+            textures: None,
+            frames: 0,
+            elapsed: 0.,
+        },
+    );
+}
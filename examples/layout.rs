@@ -0,0 +1,101 @@
+use porcelain::{
+    color::Color,
+    conf::WindowConfig,
+    layout::{
+        AlignX, AlignY, ChildAlignment, ElementConfig, Layout, LayoutDirection, Padding, Sizing,
+        SizingAxis,
+    },
+    render::EventListener,
+    start,
+};
+
+struct AppState {}
+
+impl EventListener for AppState {
+    fn update(&mut self, _texture_context: &porcelain::texture::TextureContext, _dt: f64) {
+        // no-op
+    }
+
+    fn draw(&self, draw_context: &mut porcelain::render::DrawContext) {
+        let (width, height) = draw_context.screen_size();
+        let grow = Sizing {
+            width: SizingAxis::grow(),
+            height: SizingAxis::grow(),
+        };
+
+        let mut layout = Layout::with_root(ElementConfig {
+            sizing: Sizing {
+                width: SizingAxis::Fixed(width),
+                height: SizingAxis::Fixed(height),
+            },
+            padding: Padding::all(16.),
+            child_gap: 16.,
+            background: Some(Color::from_rgba8(43, 41, 51, 255)),
+            ..Default::default()
+        });
+
+        // Sidebar
+        layout.element(
+            ElementConfig {
+                sizing: Sizing {
+                    width: SizingAxis::Fixed(250.),
+                    height: SizingAxis::grow(),
+                },
+                padding: Padding::all(16.),
+                child_gap: 8.,
+                direction: LayoutDirection::TopToBottom,
+                background: Some(Color::from_rgba8(90, 90, 110, 255)),
+                ..Default::default()
+            },
+            |layout| {
+                for _ in 0..5 {
+                    layout.leaf(ElementConfig {
+                        sizing: Sizing {
+                            width: SizingAxis::grow(),
+                            height: SizingAxis::Fixed(48.),
+                        },
+                        background: Some(Color::from_rgba8(225, 138, 50, 255)),
+                        ..Default::default()
+                    });
+                }
+            },
+        );
+
+        // Main content, with a centered card
+        layout.element(
+            ElementConfig {
+                sizing: grow,
+                child_alignment: ChildAlignment {
+                    x: AlignX::Center,
+                    y: AlignY::Center,
+                },
+                background: Some(Color::from_rgba8(70, 70, 85, 255)),
+                ..Default::default()
+            },
+            |layout| {
+                layout.leaf(ElementConfig {
+                    sizing: Sizing {
+                        width: SizingAxis::Percent(0.5),
+                        height: SizingAxis::Fixed(200.),
+                    },
+                    background: Some(Color::from_rgba8(168, 66, 28, 255)),
+                    ..Default::default()
+                });
+            },
+        );
+
+        layout.end(draw_context);
+    }
+}
+
+fn main() {
+    let window_config = WindowConfig {
+        window_title: "Layout".to_owned(),
+        resizable: true,
+        ..Default::default()
+    };
+
+    let app_state = AppState {};
+
+    start(window_config, app_state);
+}
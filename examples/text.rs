@@ -0,0 +1,67 @@
+use porcelain::{
+    color::Color, conf::WindowConfig, render::EventListener, start, text::FontId,
+    texture::TextureContext,
+};
+
+const FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSansMono.ttf");
+
+struct AppState {
+    font: Option<FontId>,
+    elapsed: f64,
+}
+
+impl EventListener for AppState {
+    fn update(&mut self, texture_context: &TextureContext, dt: f64) {
+        if self.font.is_none() {
+            self.font = Some(texture_context.load_font(FONT).unwrap());
+        }
+        self.elapsed += dt;
+    }
+
+    fn draw(&self, draw_context: &mut porcelain::render::DrawContext) {
+        let Some(font) = self.font else {
+            return;
+        };
+
+        draw_context.draw_text(
+            "The quick brown fox\njumps over the lazy dog",
+            40.,
+            40.,
+            font,
+            32.,
+            Color::from_rgba8(255, 255, 255, 255),
+        );
+
+        let label = format!("{:.1} seconds", self.elapsed);
+        let dimensions = draw_context.measure_text(&label, font, 24.);
+        draw_context.draw_rect(
+            40.,
+            160.,
+            dimensions.width + 16.,
+            dimensions.height + 16.,
+            Color::from_rgba8(60, 80, 160, 255),
+        );
+        draw_context.draw_text(
+            &label,
+            48.,
+            168.,
+            font,
+            24.,
+            Color::from_rgba8(255, 220, 120, 255),
+        );
+    }
+}
+
+fn main() {
+    let window_config = WindowConfig {
+        window_title: "Text".to_owned(),
+        ..Default::default()
+    };
+
+    let app_state = AppState {
+        font: None,
+        elapsed: 0.,
+    };
+
+    start(window_config, app_state);
+}
@@ -0,0 +1,174 @@
+//! Rectangle packing shared by the glyph cache and the image atlas of `TextureContext`.
+//!
+//! Many small images packed onto a few shared pages can be drawn with a handful of textures,
+//! so they batch into the same draw calls.
+
+use glam::Vec2;
+use miniquad::{TextureFormat, TextureId};
+
+use crate::{backend::Backend, render::TextureArea, texture::TextureOptions};
+
+const PAGE_SIZE: u16 = 1024;
+// Border around each image, filled with its edge pixels so linear filtering does not
+// bleed neighbours in.
+const IMAGE_PADDING: u16 = 1;
+
+struct Shelf {
+    y: u16,
+    height: u16,
+    cursor_x: u16,
+}
+
+/// Packs rectangles into rows of increasing height, each going into the tightest row it fits in.
+pub(crate) struct ShelfPacker {
+    width: u16,
+    height: u16,
+    shelves: Vec<Shelf>,
+}
+
+impl ShelfPacker {
+    pub(crate) fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Reserve a `w` x `h` area, returning its top-left corner, or `None` when it does not fit.
+    pub(crate) fn allocate(&mut self, w: u16, h: u16) -> Option<(u16, u16)> {
+        if w > self.width || h > self.height {
+            return None;
+        }
+
+        let last = self.shelves.len().checked_sub(1);
+        let fitting = self
+            .shelves
+            .iter()
+            .enumerate()
+            .filter(|(i, shelf)| {
+                let fits_height = shelf.height >= h
+                    // The last shelf can still grow, nothing is below it
+                    || (Some(*i) == last && shelf.y + h <= self.height);
+                fits_height && shelf.cursor_x + w <= self.width
+            })
+            .min_by_key(|(_, shelf)| shelf.height.abs_diff(h))
+            .map(|(i, _)| i);
+
+        let shelf = match fitting {
+            Some(i) => &mut self.shelves[i],
+            None => {
+                let y = self
+                    .shelves
+                    .last()
+                    .map_or(0, |shelf| shelf.y + shelf.height);
+                if y + h > self.height {
+                    return None;
+                }
+                self.shelves.push(Shelf {
+                    y,
+                    height: h,
+                    cursor_x: 0,
+                });
+                self.shelves.last_mut().unwrap()
+            }
+        };
+
+        let location = (shelf.cursor_x, shelf.y);
+        shelf.cursor_x += w;
+        shelf.height = shelf.height.max(h);
+
+        Some(location)
+    }
+}
+
+struct AtlasPage {
+    texture: TextureId,
+    packer: ShelfPacker,
+}
+
+/// RGBA8 images packed onto shared pages, a new page being created whenever they are all full.
+pub(crate) struct ImageAtlas {
+    pages: Vec<AtlasPage>,
+}
+
+impl ImageAtlas {
+    pub(crate) fn new() -> Self {
+        Self { pages: Vec::new() }
+    }
+
+    pub(crate) fn add(
+        &mut self,
+        backend: &mut dyn Backend,
+        width: u16,
+        height: u16,
+        buffer: &[u8],
+    ) -> TextureArea {
+        // Pages shrink to fit backends that cannot create textures that large
+        let page_size = u16::try_from(backend.max_texture_size())
+            .map_or(PAGE_SIZE, |max_size| PAGE_SIZE.min(max_size));
+        let padded_w = width.saturating_add(IMAGE_PADDING * 2);
+        let padded_h = height.saturating_add(IMAGE_PADDING * 2);
+
+        // Images larger than a page get a texture of their own
+        if padded_w > page_size || padded_h > page_size {
+            return TextureArea {
+                texture: backend.new_texture(
+                    width,
+                    height,
+                    TextureFormat::RGBA8,
+                    buffer,
+                    TextureOptions::default(),
+                ),
+                location: Vec2::ZERO,
+                size: Vec2::ONE,
+            };
+        }
+
+        let allocated = self.pages.iter_mut().find_map(|page| {
+            let location = page.packer.allocate(padded_w, padded_h)?;
+            Some((page.texture, location))
+        });
+        let (texture, (x, y)) = match allocated {
+            Some(allocated) => allocated,
+            None => {
+                let pixels = vec![0; page_size as usize * page_size as usize * 4];
+                let mut page = AtlasPage {
+                    texture: backend.new_texture(
+                        page_size,
+                        page_size,
+                        TextureFormat::RGBA8,
+                        &pixels,
+                        TextureOptions::default(),
+                    ),
+                    packer: ShelfPacker::new(page_size, page_size),
+                };
+                let location = page
+                    .packer
+                    .allocate(padded_w, padded_h)
+                    .expect("An image smaller than a page fits on an empty one");
+                let texture = page.texture;
+                self.pages.push(page);
+                (texture, location)
+            }
+        };
+
+        let mut padded = Vec::with_capacity(padded_w as usize * padded_h as usize * 4);
+        for padded_y in 0..padded_h {
+            let source_y = padded_y.saturating_sub(IMAGE_PADDING).min(height - 1) as usize;
+            for padded_x in 0..padded_w {
+                let source_x = padded_x.saturating_sub(IMAGE_PADDING).min(width - 1) as usize;
+                let index = (source_y * width as usize + source_x) * 4;
+                padded.extend_from_slice(&buffer[index..index + 4]);
+            }
+        }
+        backend.update_texture_part(texture, x, y, padded_w, padded_h, &padded);
+
+        let page_size = page_size as f32;
+        TextureArea {
+            texture,
+            location: Vec2::new((x + IMAGE_PADDING) as f32, (y + IMAGE_PADDING) as f32) / page_size,
+            size: Vec2::new(width as f32, height as f32) / page_size,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use miniquad::{TextureFormat, TextureId};
+
+use crate::{
+    input::DroppedFile,
+    material::{MaterialError, MaterialId, MaterialParams},
+    render::DrawContext,
+    texture::TextureOptions,
+};
+
+mod gpu;
+mod software;
+
+pub use gpu::GpuBackend;
+pub use software::SoftwareBackend;
+
+/// Consumes the geometry recorded into a `DrawContext` and turns it into pixels.
+///
+/// `RendererContext` only talks to this trait, so the same draw code can target the
+/// miniquad window or the CPU rasterizer.
+pub trait Backend {
+    fn new_texture(
+        &mut self,
+        width: u16,
+        height: u16,
+        format: TextureFormat,
+        buffer: &[u8],
+        options: TextureOptions,
+    ) -> TextureId;
+
+    /// Free a texture or render target, which must not be drawn with afterwards.
+    fn delete_texture(&mut self, texture: TextureId);
+
+    /// RGBA8 texture that draw calls can target, transparent until drawn into.
+    fn new_render_target(&mut self, width: u16, height: u16) -> TextureId;
+
+    /// Compile the shaders of a material, returning an error when they do not build.
+    fn new_material(&mut self, params: &MaterialParams) -> Result<MaterialId, MaterialError>;
+
+    /// Overwrite a `width` x `height` area at (`x`, `y`) with `buffer`, in the texture's format.
+    fn update_texture_part(
+        &mut self,
+        texture: TextureId,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        buffer: &[u8],
+    );
+
+    /// Called with the new window size in physical pixels.
+    fn resize(&mut self, width: f32, height: f32);
+
+    /// Size of the drawable area in logical units, as seen by `DrawContext`.
+    fn screen_size(&self) -> (f32, f32);
+
+    /// Physical pixels per logical unit.
+    fn dpi_scale(&self) -> f32;
+
+    /// Largest width or height of a texture, in pixels.
+    fn max_texture_size(&self) -> u32;
+
+    /// Whether draw calls can use 32-bit indices, for `IndexFormat::Auto`.
+    fn supports_u32_indices(&self) -> bool;
+
+    /// Keep the window open after a quit request that `EventListener::quit_requested` refused.
+    fn cancel_quit(&mut self);
+
+    /// Files of the last drop on the window.
+    fn dropped_files(&self) -> Vec<DroppedFile>;
+
+    fn render(&mut self, draw_context: &DrawContext);
+}
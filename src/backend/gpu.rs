@@ -0,0 +1,772 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use glam::{Mat4, Vec4};
+use miniquad::{
+    Bindings, BlendFactor, BlendState, BlendValue, BufferId, BufferLayout, Equation,
+    MipmapFilterMode, PassAction, Pipeline, PipelineParams, RenderPass, RenderingBackend, ShaderId,
+    TextureFormat, TextureId, TextureParams, UniformDesc, UniformsSource, VertexAttribute, window,
+};
+
+use crate::{
+    backend::Backend,
+    conf::{IndexFormat, WindowConfig},
+    error::Error,
+    input::DroppedFile,
+    material::{MaterialError, MaterialId, MaterialParams},
+    render::{BlendMode, DrawCall, DrawContext, Shading, Vertex},
+    shader::{self, BoxShadowUniforms, Uniforms},
+    texture::TextureOptions,
+};
+
+pub struct GpuBackend {
+    shader: ShaderId,
+    box_shadow_shader: ShaderId,
+    // Indexed by `MaterialId`
+    material_shaders: Vec<ShaderId>,
+    // Built the first time a shader is drawn with a blend mode
+    pipelines: HashMap<(ShaderId, BlendMode), Pipeline>,
+    bindings: Bindings,
+    // Vertex and index buffers, one pair per segment of the frame; usually only the first one
+    buffers: Vec<(BufferId, BufferId)>,
+    max_vertices_per_draw: usize,
+    max_indices_per_draw: usize,
+    index_format: IndexFormat,
+    supports_u32_indices: bool,
+    max_texture_size: u32,
+    // Indices of the frame rebased on the first vertex of their segment, reused between frames
+    segment_indices: Vec<u32>,
+    // `segment_indices` narrowed down for 16-bit index buffers
+    narrow_indices: Vec<u16>,
+    uniform: Uniforms,
+    screen_size: (f32, f32),
+    render_passes: HashMap<TextureId, RenderPass>,
+    // Regenerated after every update so scaled down draws see the new pixels
+    mipmapped_textures: HashSet<TextureId>,
+    backend_info: miniquad::Backend,
+    context: Box<dyn RenderingBackend>,
+}
+
+impl GpuBackend {
+    /// Must be called after the miniquad window has been created.
+    pub fn new(config: &WindowConfig) -> Self {
+        Self::try_new(config).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `new`, returning an error when the built-in shaders do not build on this driver.
+    pub fn try_new(config: &WindowConfig) -> Result<Self, Error> {
+        let mut context = window::new_rendering_backend();
+
+        let info = context.info();
+        let backend_info = info.backend;
+        // Core in OpenGL 3 and OpenGL ES 3, which miniquad only tells apart by GLSL versions
+        let supports_u32_indices = backend_info == miniquad::Backend::Metal
+            || info.glsl_support.v130
+            || info.glsl_support.v150
+            || info.glsl_support.v330
+            || info.glsl_support.v300es;
+        let index_format = config.index_format.resolve(supports_u32_indices)?;
+        let max_texture_size = match backend_info {
+            miniquad::Backend::OpenGl => {
+                let mut size = 0;
+                unsafe {
+                    miniquad::gl::glGetIntegerv(miniquad::gl::GL_MAX_TEXTURE_SIZE, &mut size);
+                }
+                size as u32
+            }
+            // Metal only has fixed limits per GPU family, the lowest being on A7 and A8 iOS devices
+            miniquad::Backend::Metal if cfg!(target_os = "macos") => 16384,
+            miniquad::Backend::Metal => 8192,
+        };
+
+        let white_texture = context.new_texture_from_rgba8(1, 1, &[255, 255, 255, 255]);
+
+        // Indices are absolute within a segment, so no segment can reach further than them
+        let max_vertices_per_draw = config
+            .max_vertices_per_draw
+            .min(index_format.max_vertices());
+        let max_indices_per_draw = config.max_indices_per_draw;
+        let (vertex_buffer, index_buffer) = new_buffers(
+            &mut *context,
+            max_vertices_per_draw,
+            max_indices_per_draw,
+            index_format,
+        );
+
+        let bindings = Bindings {
+            vertex_buffers: vec![vertex_buffer],
+            index_buffer,
+            images: vec![white_texture],
+        };
+
+        let shader = context
+            .new_shader(
+                match backend_info {
+                    miniquad::Backend::OpenGl => miniquad::ShaderSource::Glsl {
+                        vertex: shader::VERTEX,
+                        fragment: shader::FRAGMENT,
+                    },
+                    miniquad::Backend::Metal => miniquad::ShaderSource::Msl {
+                        program: shader::METAL,
+                    },
+                },
+                shader::meta(),
+            )
+            .map_err(|error| Error::Shader(error.to_string()))?;
+
+        let box_shadow_shader = context
+            .new_shader(
+                match backend_info {
+                    miniquad::Backend::OpenGl => miniquad::ShaderSource::Glsl {
+                        vertex: shader::BOX_SHADOW_VERTEX,
+                        fragment: shader::BOX_SHADOW_FRAGMENT,
+                    },
+                    miniquad::Backend::Metal => miniquad::ShaderSource::Msl {
+                        program: shader::BOX_SHADOW_METAL,
+                    },
+                },
+                shader::box_shadow_meta(),
+            )
+            .map_err(|error| Error::Shader(error.to_string()))?;
+
+        let (width, height) = window::screen_size();
+        let dpi = window::dpi_scale();
+
+        Ok(Self {
+            shader,
+            box_shadow_shader,
+            material_shaders: Vec::new(),
+            pipelines: HashMap::new(),
+            bindings,
+            buffers: vec![(vertex_buffer, index_buffer)],
+            max_vertices_per_draw,
+            max_indices_per_draw,
+            index_format,
+            supports_u32_indices,
+            max_texture_size,
+            segment_indices: Vec::new(),
+            narrow_indices: Vec::new(),
+            uniform: Uniforms {
+                model: Mat4::IDENTITY,
+                projection: Mat4::orthographic_rh_gl(0., width / dpi, height / dpi, 0., -1., 1.),
+            },
+            screen_size: (width / dpi, height / dpi),
+            render_passes: HashMap::new(),
+            mipmapped_textures: HashSet::new(),
+            backend_info,
+            context,
+        })
+    }
+}
+
+impl Backend for GpuBackend {
+    fn new_texture(
+        &mut self,
+        width: u16,
+        height: u16,
+        format: TextureFormat,
+        buffer: &[u8],
+        options: TextureOptions,
+    ) -> TextureId {
+        let texture = self.context.new_texture_from_data_and_format(
+            buffer,
+            TextureParams {
+                width: width as u32,
+                height: height as u32,
+                format,
+                wrap: options.wrap,
+                min_filter: options.filter,
+                mag_filter: options.filter,
+                mipmap_filter: if options.mipmaps {
+                    MipmapFilterMode::Linear
+                } else {
+                    MipmapFilterMode::None
+                },
+                allocate_mipmaps: options.mipmaps,
+                ..Default::default()
+            },
+        );
+
+        if options.mipmaps {
+            self.context.texture_generate_mipmaps(texture);
+            self.mipmapped_textures.insert(texture);
+        }
+        texture
+    }
+
+    fn new_render_target(&mut self, width: u16, height: u16) -> TextureId {
+        let texture = self.context.new_render_texture(TextureParams {
+            width: width as u32,
+            height: height as u32,
+            format: TextureFormat::RGBA8,
+            ..Default::default()
+        });
+        let pass = self.context.new_render_pass(texture, None);
+
+        // Render textures start with undefined content
+        self.context
+            .begin_pass(Some(pass), PassAction::clear_color(0., 0., 0., 0.));
+        self.context.end_render_pass();
+
+        self.render_passes.insert(texture, pass);
+        texture
+    }
+
+    fn new_material(&mut self, params: &MaterialParams) -> Result<MaterialId, MaterialError> {
+        let mut meta = shader::meta();
+        meta.uniforms.uniforms.extend(
+            params
+                .uniforms
+                .iter()
+                .map(|(name, value)| UniformDesc::new(name, value.uniform_type())),
+        );
+
+        let shader = self
+            .context
+            .new_shader(
+                match self.backend_info {
+                    miniquad::Backend::OpenGl => miniquad::ShaderSource::Glsl {
+                        vertex: params.vertex,
+                        fragment: params.fragment,
+                    },
+                    miniquad::Backend::Metal => miniquad::ShaderSource::Msl {
+                        program: params.metal,
+                    },
+                },
+                meta,
+            )
+            .map_err(|error| MaterialError::new(error.to_string()))?;
+
+        self.material_shaders.push(shader);
+        Ok(MaterialId(self.material_shaders.len() - 1))
+    }
+
+    fn update_texture_part(
+        &mut self,
+        texture: TextureId,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        buffer: &[u8],
+    ) {
+        self.context.texture_update_part(
+            texture,
+            x as i32,
+            y as i32,
+            width as i32,
+            height as i32,
+            buffer,
+        );
+
+        if self.mipmapped_textures.contains(&texture) {
+            self.context.texture_generate_mipmaps(texture);
+        }
+    }
+
+    fn delete_texture(&mut self, texture: TextureId) {
+        self.mipmapped_textures.remove(&texture);
+        match self.render_passes.remove(&texture) {
+            // Deleting a pass also deletes its texture
+            Some(pass) => self.context.delete_render_pass(pass),
+            None => self.context.delete_texture(texture),
+        }
+    }
+
+    fn resize(&mut self, width: f32, height: f32) {
+        let dpi = window::dpi_scale();
+
+        self.uniform.projection =
+            Mat4::orthographic_rh_gl(0., width / dpi, height / dpi, 0., -1., 1.);
+        self.screen_size = (width / dpi, height / dpi);
+    }
+
+    fn screen_size(&self) -> (f32, f32) {
+        self.screen_size
+    }
+
+    fn dpi_scale(&self) -> f32 {
+        window::dpi_scale()
+    }
+
+    fn max_texture_size(&self) -> u32 {
+        self.max_texture_size
+    }
+
+    fn supports_u32_indices(&self) -> bool {
+        self.supports_u32_indices
+    }
+
+    fn cancel_quit(&mut self) {
+        window::cancel_quit();
+    }
+
+    fn dropped_files(&self) -> Vec<DroppedFile> {
+        (0..window::dropped_file_count())
+            .map(|index| DroppedFile {
+                path: window::dropped_file_path(index),
+                bytes: window::dropped_file_bytes(index),
+            })
+            .collect()
+    }
+
+    fn render(&mut self, draw_context: &DrawContext) {
+        let draw_calls = &draw_context.draw_call_vec;
+
+        // The whole frame is uploaded before drawing, each segment into buffers of its own so
+        // no upload overwrites vertices an earlier draw still reads.
+        let segments = segments(
+            draw_calls,
+            self.max_vertices_per_draw,
+            self.max_indices_per_draw,
+        );
+        self.segment_indices.clear();
+        for (segment, draw_call_range) in segments.iter().enumerate() {
+            if segment == self.buffers.len() {
+                let buffers = new_buffers(
+                    &mut *self.context,
+                    self.max_vertices_per_draw,
+                    self.max_indices_per_draw,
+                    self.index_format,
+                );
+                self.buffers.push(buffers);
+            }
+            let (vertex_buffer, index_buffer) = self.buffers[segment];
+
+            let first = &draw_calls[draw_call_range.start];
+            let last = &draw_calls[draw_call_range.end - 1];
+            let vertex_offset = first.vertex_indices_slice.offset;
+            let vertex_end = last.vertex_indices_slice.offset + last.vertex_indices_slice.length;
+            self.context.buffer_update(
+                vertex_buffer,
+                miniquad::BufferSource::slice(
+                    &draw_context.vertex_buffer[vertex_offset..vertex_end],
+                ),
+            );
+
+            let index_start = self.segment_indices.len();
+            rebase_indices(
+                &draw_calls[draw_call_range.clone()],
+                &draw_context.index_buffer,
+                &mut self.segment_indices,
+            );
+            let indices = &self.segment_indices[index_start..];
+            if self.index_format == IndexFormat::U32 {
+                self.context
+                    .buffer_update(index_buffer, miniquad::BufferSource::slice(indices));
+            } else {
+                narrow_indices(indices, &mut self.narrow_indices);
+                self.context.buffer_update(
+                    index_buffer,
+                    miniquad::BufferSource::slice(&self.narrow_indices),
+                );
+            }
+        }
+
+        let context = &mut self.context;
+
+        // [TODO] Expose the clear color to the user
+        // Technically can be exposed via drawing a rect but
+        context.clear(Some((0., 0., 0., 255.)), None, None);
+
+        // Target of the pass currently open, `Some(None)` being the screen
+        let mut open_pass: Option<Option<TextureId>> = None;
+        for (segment, draw_call_range) in segments.into_iter().enumerate() {
+            (self.bindings.vertex_buffers[0], self.bindings.index_buffer) = self.buffers[segment];
+            let index_offset = draw_calls[draw_call_range.start].index_indices_slice.offset;
+
+            for draw_call in &draw_calls[draw_call_range] {
+                let target = draw_call.target.map(|target| target.texture);
+                // A clear can only happen when a pass begins
+                if open_pass != Some(target) || draw_call.clear.is_some() {
+                    if open_pass.is_some() {
+                        context.end_render_pass();
+                    }
+                    let action = match draw_call.clear {
+                        Some(color) => {
+                            let [r, g, b, a] = color.into();
+                            PassAction::clear_color(r, g, b, a)
+                        }
+                        None => PassAction::Nothing,
+                    };
+                    match target {
+                        Some(texture) => {
+                            context.begin_pass(Some(self.render_passes[&texture]), action)
+                        }
+                        None => context.begin_default_pass(action),
+                    }
+                    open_pass = Some(target);
+                }
+
+                self.bindings.images[0] = draw_call.texture;
+
+                let shader = match (&draw_call.shading, &draw_call.material) {
+                    (Shading::Textured, Some(material)) => self.material_shaders[material.id.0],
+                    (Shading::Textured, None) => self.shader,
+                    (Shading::BoxShadow { .. }, _) => self.box_shadow_shader,
+                };
+                let pipeline = *self
+                    .pipelines
+                    .entry((shader, draw_call.blend_mode))
+                    .or_insert_with(|| new_pipeline(&mut **context, shader, draw_call.blend_mode));
+                context.apply_pipeline(&pipeline);
+
+                // OpenGL keeps the first row of a texture at the bottom of its framebuffer, so render
+                // targets are drawn upside down there to come out the right way up as a `TextureArea`.
+                let flipped =
+                    draw_call.target.is_some() && self.backend_info == miniquad::Backend::OpenGl;
+                let (projection, (framebuffer_width, framebuffer_height), dpi_scale) =
+                    match draw_call.target {
+                        Some(target) => {
+                            let (width, height) = (target.width as f32, target.height as f32);
+                            let projection = if flipped {
+                                Mat4::orthographic_rh_gl(0., width, 0., height, -1., 1.)
+                            } else {
+                                Mat4::orthographic_rh_gl(0., width, height, 0., -1., 1.)
+                            };
+                            (projection, (width, height), 1.)
+                        }
+                        None => (
+                            self.uniform.projection,
+                            window::screen_size(),
+                            window::dpi_scale(),
+                        ),
+                    };
+
+                // Scissor testing is always on once a pipeline is applied, so unclipped calls get the whole framebuffer.
+                let (x, y, w, h) = match draw_call.clip {
+                    Some(clip) => clip.to_pixels(dpi_scale),
+                    None => (0, 0, framebuffer_width as i32, framebuffer_height as i32),
+                };
+                let y = scissor_y(y, h, flipped, window::screen_size().1 as i32);
+                context.apply_scissor_rect(x, y, w, h);
+
+                context.apply_bindings(&self.bindings);
+                match draw_call.shading {
+                    Shading::Textured if let Some(material) = &draw_call.material => {
+                        let mut data = Vec::with_capacity(32 + material.values.len() * 4);
+                        data.extend(self.uniform.model.to_cols_array());
+                        data.extend(projection.to_cols_array());
+                        for value in &material.values {
+                            value.write(&mut data);
+                        }
+                        context.apply_uniforms_from_bytes(
+                            data.as_ptr() as *const u8,
+                            data.len() * size_of::<f32>(),
+                        );
+                    }
+                    Shading::Textured => context.apply_uniforms(UniformsSource::table(&Uniforms {
+                        model: self.uniform.model,
+                        projection,
+                    })),
+                    Shading::BoxShadow {
+                        half_size,
+                        corner_radius,
+                        sigma,
+                    } => context.apply_uniforms(UniformsSource::table(&BoxShadowUniforms {
+                        model: self.uniform.model,
+                        projection,
+                        shadow: Vec4::new(half_size.x, half_size.y, corner_radius, sigma),
+                    })),
+                }
+
+                context.draw(
+                    (draw_call.index_indices_slice.offset - index_offset) as i32,
+                    draw_call.index_indices_slice.length as i32,
+                    1,
+                );
+            }
+        }
+        if open_pass.is_some() {
+            context.end_render_pass();
+        }
+
+        context.commit_frame();
+    }
+}
+
+/// Split the draw calls into runs whose vertices and indices fit in one pair of buffers.
+fn segments(
+    draw_calls: &[DrawCall],
+    max_vertices_per_draw: usize,
+    max_indices_per_draw: usize,
+) -> Vec<Range<usize>> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    while start < draw_calls.len() {
+        let vertex_offset = draw_calls[start].vertex_indices_slice.offset;
+        let index_offset = draw_calls[start].index_indices_slice.offset;
+
+        // A single draw call always fits, `DrawContext` keeps them under these limits
+        let mut end = start + 1;
+        while let Some(draw_call) = draw_calls.get(end) {
+            let vertices = draw_call.vertex_indices_slice.offset
+                + draw_call.vertex_indices_slice.length
+                - vertex_offset;
+            let indices = draw_call.index_indices_slice.offset
+                + draw_call.index_indices_slice.length
+                - index_offset;
+            if vertices > max_vertices_per_draw || indices > max_indices_per_draw {
+                break;
+            }
+            end += 1;
+        }
+
+        segments.push(start..end);
+        start = end;
+    }
+    segments
+}
+
+/// The `y` to pass `apply_scissor_rect` for a scissor `y` pixels below the top of the framebuffer.
+///
+/// miniquad takes the bottom-left corner as the origin. OpenGL keeps it there, except in render
+/// targets, which are drawn upside down (see `flipped` in `render`). Metal moves it back to the
+/// top-left corner with the height of the window, even inside render targets.
+fn scissor_y(y: i32, h: i32, flipped: bool, screen_height: i32) -> i32 {
+    if flipped { y } else { screen_height - (y + h) }
+}
+
+/// Append the indices of `draw_calls`, a segment of the frame, to `segment_indices`. Draw call
+/// indices start from their own first vertex, they are rebased on the first vertex of the segment.
+fn rebase_indices(draw_calls: &[DrawCall], index_buffer: &[u32], segment_indices: &mut Vec<u32>) {
+    let Some(first) = draw_calls.first() else {
+        return;
+    };
+    let vertex_offset = first.vertex_indices_slice.offset;
+
+    for draw_call in draw_calls {
+        let base = (draw_call.vertex_indices_slice.offset - vertex_offset) as u32;
+        let indices = &index_buffer[draw_call.index_indices_slice.offset
+            ..draw_call.index_indices_slice.offset + draw_call.index_indices_slice.length];
+        segment_indices.extend(indices.iter().map(|index| index + base));
+    }
+}
+
+/// Replace `narrow_indices` with the rebased `indices` of a segment, for a 16-bit index buffer.
+fn narrow_indices(indices: &[u32], narrow_indices: &mut Vec<u16>) {
+    narrow_indices.clear();
+    // Segments of 16-bit buffers never go over 65536 vertices
+    narrow_indices.extend(
+        indices.iter().map(|&index| {
+            u16::try_from(index).expect("16-bit segments index at most 65536 vertices")
+        }),
+    );
+}
+
+/// Stream vertex and index buffers holding up to `max_vertices` and `max_indices`.
+fn new_buffers(
+    context: &mut dyn RenderingBackend,
+    max_vertices: usize,
+    max_indices: usize,
+    index_format: IndexFormat,
+) -> (BufferId, BufferId) {
+    let vertex_buffer = context.new_buffer(
+        miniquad::BufferType::VertexBuffer,
+        miniquad::BufferUsage::Stream,
+        miniquad::BufferSource::empty::<Vertex>(max_vertices),
+    );
+    let index_buffer = context.new_buffer(
+        miniquad::BufferType::IndexBuffer,
+        miniquad::BufferUsage::Stream,
+        match index_format {
+            IndexFormat::U32 => miniquad::BufferSource::empty::<u32>(max_indices),
+            _ => miniquad::BufferSource::empty::<u16>(max_indices),
+        },
+    );
+    (vertex_buffer, index_buffer)
+}
+
+/// Pipeline for `shader` with the `Vertex` layout, blending as `blend_mode` describes.
+fn new_pipeline(
+    context: &mut dyn RenderingBackend,
+    shader: ShaderId,
+    blend_mode: BlendMode,
+) -> Pipeline {
+    use BlendFactor::{One, OneMinusValue, Value};
+    use BlendValue::{DestinationColor, SourceAlpha, SourceColor};
+
+    // Factors for the color, then for the alpha, `None` writing the source as is.
+    // Multiply and screen get premultiplied vertex colors, see `BlendMode::premultiplies_colors`.
+    let factors = match blend_mode {
+        BlendMode::Alpha => Some((
+            (Value(SourceAlpha), OneMinusValue(SourceAlpha)),
+            (One, OneMinusValue(SourceAlpha)),
+        )),
+        BlendMode::PremultipliedAlpha => Some((
+            (One, OneMinusValue(SourceAlpha)),
+            (One, OneMinusValue(SourceAlpha)),
+        )),
+        BlendMode::Additive => Some(((Value(SourceAlpha), One), (One, One))),
+        BlendMode::Multiply => Some((
+            (Value(DestinationColor), OneMinusValue(SourceAlpha)),
+            (One, OneMinusValue(SourceAlpha)),
+        )),
+        BlendMode::Screen => Some((
+            (One, OneMinusValue(SourceColor)),
+            (One, OneMinusValue(SourceAlpha)),
+        )),
+        BlendMode::Replace => None,
+    };
+    let (color_blend, alpha_blend) = match factors {
+        Some(((color_source, color_destination), (alpha_source, alpha_destination))) => (
+            Some(BlendState::new(
+                Equation::Add,
+                color_source,
+                color_destination,
+            )),
+            Some(BlendState::new(
+                Equation::Add,
+                alpha_source,
+                alpha_destination,
+            )),
+        ),
+        None => (None, None),
+    };
+
+    context.new_pipeline(
+        &[BufferLayout::default()],
+        &[
+            VertexAttribute::new("in_pos", miniquad::VertexFormat::Float2),
+            VertexAttribute::new("in_color", miniquad::VertexFormat::Float4),
+            VertexAttribute::new("in_texcoord", miniquad::VertexFormat::Float2),
+        ],
+        shader,
+        PipelineParams {
+            color_blend,
+            alpha_blend,
+            ..Default::default()
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use miniquad::RawId;
+
+    use super::*;
+    use crate::render::VecSlice;
+
+    /// A draw call over `vertices` and `indices` of the frame buffers.
+    fn draw_call(vertices: Range<usize>, indices: Range<usize>) -> DrawCall {
+        DrawCall {
+            vertex_indices_slice: VecSlice {
+                offset: vertices.start,
+                length: vertices.len(),
+            },
+            index_indices_slice: VecSlice {
+                offset: indices.start,
+                length: indices.len(),
+            },
+            texture: TextureId::from_raw_id(RawId::OpenGl(0)),
+            clip: None,
+            shading: Shading::Textured,
+            material: None,
+            blend_mode: BlendMode::default(),
+            target: None,
+            clear: None,
+        }
+    }
+
+    /// What Metal makes of a scissor `y` given to `apply_scissor_rect`, from its top-left corner.
+    fn metal_scissor_top(y: i32, h: i32, screen_height: i32) -> i32 {
+        screen_height - (y + h)
+    }
+
+    #[test]
+    fn scissor_is_flipped_on_the_opengl_screen() {
+        assert_eq!(scissor_y(10, 20, false, 600), 570);
+    }
+
+    #[test]
+    fn scissor_is_kept_in_flipped_opengl_targets() {
+        assert_eq!(scissor_y(10, 20, true, 600), 10);
+    }
+
+    #[test]
+    fn metal_scissor_lands_on_the_clip_in_targets() {
+        // A clip 10 pixels below the top of a 100 pixel high target, in a 600 pixel high window
+        let y = scissor_y(10, 20, false, 600);
+
+        assert_eq!(metal_scissor_top(y, 20, 600), 10);
+        assert_eq!(
+            metal_scissor_top(scissor_y(0, 100, false, 600), 100, 600),
+            0
+        );
+    }
+
+    /// `count` draw calls of a quad each, one after the other in the frame buffers.
+    fn quads(count: usize) -> Vec<DrawCall> {
+        (0..count)
+            .map(|quad| draw_call(quad * 4..quad * 4 + 4, quad * 6..quad * 6 + 6))
+            .collect()
+    }
+
+    #[test]
+    fn frame_under_the_limits_is_one_segment() {
+        assert_eq!(
+            segments(&quads(3), 12, 18),
+            vec![Range { start: 0, end: 3 }]
+        );
+        assert!(segments(&[], 12, 18).is_empty());
+    }
+
+    #[test]
+    fn segments_split_at_max_vertices_per_draw() {
+        assert_eq!(segments(&quads(5), 10, 1000), [0..2, 2..4, 4..5]);
+    }
+
+    #[test]
+    fn segments_split_at_max_indices_per_draw() {
+        assert_eq!(segments(&quads(5), 1000, 17), [0..2, 2..4, 4..5]);
+    }
+
+    #[test]
+    fn segments_start_from_their_first_draw_call() {
+        // Offsets are counted from the start of the segment, not of the frame
+        let draw_calls = [
+            draw_call(0..6, 0..6),
+            draw_call(6..10, 6..12),
+            draw_call(10..14, 12..18),
+            draw_call(14..20, 18..24),
+        ];
+
+        assert_eq!(segments(&draw_calls, 10, 1000), [0..2, 2..4]);
+    }
+
+    #[test]
+    fn indices_are_rebased_on_the_segment() {
+        let index_buffer = [9, 9, 0, 1, 2, 0, 1, 3];
+        let draw_calls = [draw_call(100..104, 2..5), draw_call(104..108, 5..8)];
+        let mut segment_indices = vec![7];
+
+        rebase_indices(&draw_calls, &index_buffer, &mut segment_indices);
+
+        assert_eq!(segment_indices, [7, 0, 1, 2, 4, 5, 7]);
+    }
+
+    #[test]
+    fn last_vertex_of_a_16_bit_segment_is_indexed() {
+        // A full 65536 vertex segment, starting past the reach of 16-bit indices
+        let index_buffer = [0, 65531, 0, 3];
+        let draw_calls = [
+            draw_call(70000..135532, 0..2),
+            draw_call(135532..135536, 2..4),
+        ];
+        let mut segment_indices = Vec::new();
+        let mut narrow = vec![1, 2, 3];
+
+        rebase_indices(&draw_calls, &index_buffer, &mut segment_indices);
+        narrow_indices(&segment_indices, &mut narrow);
+
+        assert_eq!(narrow, [0, 65531, 65532, u16::MAX]);
+    }
+
+    #[test]
+    #[should_panic(expected = "16-bit segments index at most 65536 vertices")]
+    fn indices_past_65535_do_not_wrap_around() {
+        narrow_indices(&[0, 65536], &mut Vec::new());
+    }
+}
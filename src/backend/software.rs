@@ -0,0 +1,532 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use glam::{Vec2, Vec3, Vec4};
+use miniquad::{FilterMode, RawId, TextureFormat, TextureId, TextureWrap};
+
+use crate::{
+    backend::Backend,
+    error::Error,
+    input::DroppedFile,
+    material::{MaterialError, MaterialId, MaterialParams},
+    render::{BlendMode, DrawCall, DrawContext, Shading, Vertex},
+    texture::TextureOptions,
+};
+
+struct SoftwareTexture {
+    width: usize,
+    height: usize,
+    format: TextureFormat,
+    // Always expanded to RGBA8 on upload so sampling has a single code path.
+    pixels: Vec<[u8; 4]>,
+    options: TextureOptions,
+}
+
+/// CPU rasterizer producing an RGBA8 framebuffer, for running without a window or GPU.
+///
+/// One logical unit maps to `dpi_scale` pixels (1 by default), and the output matches the GPU pipeline:
+/// the framebuffer is cleared to opaque black and fragments are alpha blended on top.
+/// Shaders cannot run on the CPU, so draws using a material get the built-in shading instead,
+/// and textures are always sampled from their full size, ignoring mipmaps.
+pub struct SoftwareBackend {
+    width: usize,
+    height: usize,
+    dpi_scale: f32,
+    framebuffer: Vec<[u8; 4]>,
+    draw_call_count: usize,
+
+    textures: HashMap<TextureId, SoftwareTexture>,
+    next_texture_id: u32,
+    material_count: usize,
+    max_texture_size: u32,
+
+    dropped_files: Vec<DroppedFile>,
+    quit_cancelled: bool,
+}
+
+impl SoftwareBackend {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width: width as usize,
+            height: height as usize,
+            dpi_scale: 1.,
+            framebuffer: vec![[0; 4]; width as usize * height as usize],
+            draw_call_count: 0,
+            textures: HashMap::new(),
+            next_texture_id: 0,
+            material_count: 0,
+            max_texture_size: u16::MAX as u32,
+            dropped_files: Vec::new(),
+            quit_cancelled: false,
+        }
+    }
+
+    /// Render as a HiDPI window would, `width` and `height` being physical pixels.
+    pub fn with_dpi_scale(mut self, dpi_scale: f32) -> Self {
+        self.dpi_scale = dpi_scale;
+        self
+    }
+
+    /// Reject textures wider or taller than `max_texture_size`, as a GPU with that limit would.
+    pub fn with_max_texture_size(mut self, max_texture_size: u32) -> Self {
+        self.max_texture_size = max_texture_size;
+        self
+    }
+
+    /// Files reported by the next `files_dropped_event`, standing in for a drop on the window.
+    pub fn with_dropped_files(mut self, dropped_files: Vec<DroppedFile>) -> Self {
+        self.dropped_files = dropped_files;
+        self
+    }
+
+    /// Whether a quit request was cancelled, where a window would have stayed open.
+    pub fn quit_cancelled(&self) -> bool {
+        self.quit_cancelled
+    }
+
+    /// Draw calls the last rendered frame was split into.
+    pub fn draw_call_count(&self) -> usize {
+        self.draw_call_count
+    }
+
+    /// Textures created and not yet deleted, render targets included.
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Row-major RGBA8 pixels of the last rendered frame, top row first.
+    pub fn pixels(&self) -> &[u8] {
+        self.framebuffer.as_flattened()
+    }
+
+    fn clear(&mut self) {
+        self.framebuffer.fill([0, 0, 0, 255]);
+    }
+
+    /// Rasterize a draw call into the screen or its render target.
+    fn draw(&mut self, draw_context: &DrawContext, draw_call: &DrawCall) {
+        // The destination is taken out while drawing so the textures can still be sampled,
+        // a render target never being drawn into itself.
+        let (mut pixels, width, height, dpi_scale) = match draw_call.target {
+            Some(target) => {
+                let texture = self
+                    .textures
+                    .get_mut(&target.texture)
+                    .expect("Render target was not created by this backend");
+                (
+                    std::mem::take(&mut texture.pixels),
+                    texture.width,
+                    texture.height,
+                    1.,
+                )
+            }
+            None => (
+                std::mem::take(&mut self.framebuffer),
+                self.width,
+                self.height,
+                self.dpi_scale,
+            ),
+        };
+        let mut destination = Destination {
+            pixels: &mut pixels,
+            width,
+            height,
+            dpi_scale,
+        };
+
+        if let Some(color) = draw_call.clear {
+            destination
+                .pixels
+                .fill(to_rgba8(Vec4::from(<[f32; 4]>::from(color))));
+        }
+
+        let texture = self
+            .textures
+            .get(&draw_call.texture)
+            .expect("Texture was not created by this backend");
+        let vertex_offset = draw_call.vertex_indices_slice.offset;
+        let index_offset = draw_call.index_indices_slice.offset;
+        let indices = &draw_context.index_buffer
+            [index_offset..index_offset + draw_call.index_indices_slice.length];
+
+        // Indices are relative to the start of the draw call, as they would be on the GPU.
+        for triangle in indices.chunks_exact(3) {
+            let vertices = [0, 1, 2]
+                .map(|i| &draw_context.vertex_buffer[vertex_offset + triangle[i] as usize]);
+
+            destination.rasterize_triangle(vertices, texture, draw_call);
+        }
+
+        match draw_call.target {
+            Some(target) => self.textures.get_mut(&target.texture).unwrap().pixels = pixels,
+            None => self.framebuffer = pixels,
+        }
+    }
+}
+
+/// Pixels being drawn into, either the framebuffer or a render target.
+struct Destination<'a> {
+    pixels: &'a mut [[u8; 4]],
+    width: usize,
+    height: usize,
+    dpi_scale: f32,
+}
+
+impl Destination<'_> {
+    fn rasterize_triangle(
+        &mut self,
+        vertices: [&Vertex; 3],
+        texture: &SoftwareTexture,
+        draw_call: &DrawCall,
+    ) {
+        let mut points = vertices.map(|vertex| Vec2::from(vertex.pos) * self.dpi_scale);
+        let mut vertices = vertices;
+
+        let mut area = edge(points[0], points[1], points[2]);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+        // No face culling on the GPU pipeline, so normalize the winding instead of skipping.
+        if area < 0. {
+            points.swap(1, 2);
+            vertices.swap(1, 2);
+            area = -area;
+        }
+
+        let (clip_min, clip_max) = match draw_call.clip {
+            // Same pixel rounding as the GPU scissor rectangle
+            Some(clip) => {
+                let (x, y, w, h) = clip.to_pixels(self.dpi_scale);
+                (
+                    Vec2::new(x as f32, y as f32).max(Vec2::ZERO),
+                    Vec2::new((x + w) as f32, (y + h) as f32),
+                )
+            }
+            None => (Vec2::ZERO, Vec2::new(self.width as f32, self.height as f32)),
+        };
+        let min = points[0]
+            .min(points[1])
+            .min(points[2])
+            .floor()
+            .max(clip_min);
+        let max = points[0]
+            .max(points[1])
+            .max(points[2])
+            .ceil()
+            .min(clip_max)
+            .min(Vec2::new(self.width as f32, self.height as f32));
+
+        for y in min.y as usize..max.y as usize {
+            for x in min.x as usize..max.x as usize {
+                let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+
+                let w0 = edge(points[1], points[2], p);
+                let w1 = edge(points[2], points[0], p);
+                let w2 = edge(points[0], points[1], p);
+
+                if !covers(w0, points[1], points[2])
+                    || !covers(w1, points[2], points[0])
+                    || !covers(w2, points[0], points[1])
+                {
+                    continue;
+                }
+
+                let weights = [w0 / area, w1 / area, w2 / area];
+                let color = interpolate(vertices.map(|v| Vec4::from(v.color)), weights);
+                let uv = interpolate(vertices.map(|v| Vec2::from(v.tex_coord)), weights);
+
+                let fragment = match draw_call.shading {
+                    Shading::Textured => color * texture.sample(uv),
+                    Shading::BoxShadow {
+                        half_size,
+                        corner_radius,
+                        sigma,
+                    } => {
+                        color
+                            * Vec4::new(1., 1., 1., box_shadow(uv, half_size, corner_radius, sigma))
+                    }
+                };
+
+                let pixel = &mut self.pixels[y * self.width + x];
+                let destination = Vec4::from(pixel.map(|channel| channel as f32 / 255.));
+                *pixel = to_rgba8(blend(fragment, destination, draw_call.blend_mode));
+            }
+        }
+    }
+}
+
+impl SoftwareTexture {
+    fn texel(&self, x: isize, y: isize) -> Vec4 {
+        let x = wrap(x, self.width, self.options.wrap);
+        let y = wrap(y, self.height, self.options.wrap);
+
+        Vec4::from(self.pixels[y * self.width + x].map(|channel| channel as f32 / 255.))
+    }
+
+    /// Bilinear or nearest sampling, following the texture options like the GPU sampler.
+    fn sample(&self, uv: Vec2) -> Vec4 {
+        if self.options.filter == FilterMode::Nearest {
+            let x = (uv.x * self.width as f32).floor() as isize;
+            let y = (uv.y * self.height as f32).floor() as isize;
+            return self.texel(x, y);
+        }
+
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = uv.y * self.height as f32 - 0.5;
+
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        let top = self.texel(x0, y0).lerp(self.texel(x0 + 1, y0), fx);
+        let bottom = self.texel(x0, y0 + 1).lerp(self.texel(x0 + 1, y0 + 1), fx);
+
+        top.lerp(bottom, fy)
+    }
+}
+
+/// Bring a texel coordinate back inside `0..size`.
+fn wrap(coordinate: isize, size: usize, wrap: TextureWrap) -> usize {
+    let size = size as isize;
+    let wrapped = match wrap {
+        TextureWrap::Clamp => coordinate.clamp(0, size - 1),
+        TextureWrap::Repeat => coordinate.rem_euclid(size),
+        TextureWrap::Mirror => {
+            let period = coordinate.rem_euclid(size * 2);
+            if period < size {
+                period
+            } else {
+                size * 2 - 1 - period
+            }
+        }
+    };
+    wrapped as usize
+}
+
+impl Backend for SoftwareBackend {
+    fn new_texture(
+        &mut self,
+        width: u16,
+        height: u16,
+        format: TextureFormat,
+        buffer: &[u8],
+        options: TextureOptions,
+    ) -> TextureId {
+        let pixels = expand_to_rgba8(format, buffer).unwrap_or_else(|error| panic!("{}", error));
+
+        let id = TextureId::from_raw_id(RawId::OpenGl(self.next_texture_id));
+        self.next_texture_id += 1;
+
+        self.textures.insert(
+            id,
+            SoftwareTexture {
+                width: width as usize,
+                height: height as usize,
+                format,
+                pixels,
+                options,
+            },
+        );
+
+        id
+    }
+
+    fn new_render_target(&mut self, width: u16, height: u16) -> TextureId {
+        let pixels = vec![0; width as usize * height as usize * 4];
+        self.new_texture(
+            width,
+            height,
+            TextureFormat::RGBA8,
+            &pixels,
+            TextureOptions::default(),
+        )
+    }
+
+    fn delete_texture(&mut self, texture: TextureId) {
+        self.textures.remove(&texture);
+    }
+
+    fn new_material(&mut self, _params: &MaterialParams) -> Result<MaterialId, MaterialError> {
+        self.material_count += 1;
+
+        Ok(MaterialId(self.material_count - 1))
+    }
+
+    fn update_texture_part(
+        &mut self,
+        texture: TextureId,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        buffer: &[u8],
+    ) {
+        let texture = self
+            .textures
+            .get_mut(&texture)
+            .expect("Texture was not created by this backend");
+        let pixels =
+            expand_to_rgba8(texture.format, buffer).unwrap_or_else(|error| panic!("{}", error));
+
+        for (row, row_pixels) in pixels.chunks_exact(width as usize).enumerate() {
+            let start = (y as usize + row) * texture.width + x as usize;
+            texture.pixels[start..start + width as usize].copy_from_slice(row_pixels);
+        }
+        debug_assert_eq!(pixels.len(), width as usize * height as usize);
+    }
+
+    fn resize(&mut self, width: f32, height: f32) {
+        self.width = width as usize;
+        self.height = height as usize;
+        self.framebuffer = vec![[0; 4]; self.width * self.height];
+    }
+
+    fn screen_size(&self) -> (f32, f32) {
+        (
+            self.width as f32 / self.dpi_scale,
+            self.height as f32 / self.dpi_scale,
+        )
+    }
+
+    fn dpi_scale(&self) -> f32 {
+        self.dpi_scale
+    }
+
+    fn max_texture_size(&self) -> u32 {
+        self.max_texture_size
+    }
+
+    fn supports_u32_indices(&self) -> bool {
+        true
+    }
+
+    fn cancel_quit(&mut self) {
+        self.quit_cancelled = true;
+    }
+
+    fn dropped_files(&self) -> Vec<DroppedFile> {
+        self.dropped_files.clone()
+    }
+
+    fn render(&mut self, draw_context: &DrawContext) {
+        self.clear();
+
+        self.draw_call_count = draw_context.draw_call_vec.len();
+        for draw_call in &draw_context.draw_call_vec {
+            self.draw(draw_context, draw_call);
+        }
+    }
+}
+
+fn expand_to_rgba8(format: TextureFormat, buffer: &[u8]) -> Result<Vec<[u8; 4]>, Error> {
+    Ok(match format {
+        TextureFormat::RGBA8 => buffer
+            .chunks_exact(4)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+            .collect(),
+        TextureFormat::RGB8 => buffer
+            .chunks_exact(3)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+        TextureFormat::Alpha => buffer.iter().map(|alpha| [0, 0, 0, *alpha]).collect(),
+        _ => return Err(Error::UnsupportedTextureFormat(format)),
+    })
+}
+
+/// Same approximation as `shader::BOX_SHADOW_FRAGMENT`, `point` being relative to the center of the box.
+fn box_shadow(point: Vec2, half_size: Vec2, corner_radius: f32, sigma: f32) -> f32 {
+    fn gaussian(x: f32, sigma: f32) -> f32 {
+        (-(x * x) / (2. * sigma * sigma)).exp() / ((2. * PI).sqrt() * sigma)
+    }
+
+    fn erf(x: f32) -> f32 {
+        let sign = x.signum();
+        let a = x.abs();
+        let t = 1. + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+        let t = t * t;
+        sign - sign / (t * t)
+    }
+
+    let shadow_x = |x: f32, y: f32| {
+        let delta = (half_size.y - corner_radius - y.abs()).min(0.);
+        let curved = half_size.x - corner_radius
+            + (corner_radius * corner_radius - delta * delta)
+                .max(0.)
+                .sqrt();
+        let scale = std::f32::consts::FRAC_1_SQRT_2 / sigma;
+        let low = 0.5 + 0.5 * erf((x - curved) * scale);
+        let high = 0.5 + 0.5 * erf((x + curved) * scale);
+        high - low
+    };
+
+    let start = (-3. * sigma).clamp(point.y - half_size.y, point.y + half_size.y);
+    let end = (3. * sigma).clamp(point.y - half_size.y, point.y + half_size.y);
+    let step = (end - start) / 4.;
+
+    (0..4)
+        .map(|i| {
+            let y = start + step * (i as f32 + 0.5);
+            shadow_x(point.x, point.y - y) * gaussian(y, sigma) * step
+        })
+        .sum()
+}
+
+fn edge(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    // Evaluated from the same end whichever way round the edge is, so triangles sharing it get
+    // exactly opposite weights and no pixel along it is dropped by both
+    if (a.x, a.y) > (b.x, b.y) {
+        return -edge(b, a, p);
+    }
+
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+// Pixels exactly on an edge only belong to one of the two triangles sharing it,
+// otherwise translucent meshes would double-draw their seams.
+fn covers(weight: f32, a: Vec2, b: Vec2) -> bool {
+    let d = b - a;
+    weight > 0. || (weight == 0. && (d.y > 0. || (d.y == 0. && d.x < 0.)))
+}
+
+fn interpolate<V>(values: [V; 3], weights: [f32; 3]) -> V
+where
+    V: std::ops::Mul<f32, Output = V> + std::ops::Add<Output = V> + Copy,
+{
+    values[0] * weights[0] + values[1] * weights[1] + values[2] * weights[2]
+}
+
+/// The same blend states as the GPU pipelines.
+fn blend(source: Vec4, destination: Vec4, blend_mode: BlendMode) -> Vec4 {
+    let (color, alpha) = (source.truncate(), source.w);
+    let below = destination.truncate();
+
+    let rgb = match blend_mode {
+        BlendMode::Alpha => color * alpha + below * (1. - alpha),
+        BlendMode::PremultipliedAlpha => color + below * (1. - alpha),
+        BlendMode::Additive => color * alpha + below,
+        BlendMode::Multiply => color * below + below * (1. - alpha),
+        BlendMode::Screen => color + below * (Vec3::ONE - color),
+        BlendMode::Replace => return source,
+    };
+    let alpha = match blend_mode {
+        BlendMode::Additive => alpha + destination.w,
+        _ => alpha + destination.w * (1. - alpha),
+    };
+
+    rgb.extend(alpha)
+}
+
+fn to_rgba8(color: Vec4) -> [u8; 4] {
+    color
+        .to_array()
+        .map(|channel| (channel.clamp(0., 1.) * 255.).round() as u8)
+}
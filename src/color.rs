@@ -1,4 +1,4 @@
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Color {
     r: f32,
     g: f32,
@@ -21,4 +21,19 @@ impl Color {
             a: a as f32 / 255.,
         }
     }
+
+    /// Same color with its alpha replaced, `alpha` going from 0 to 1.
+    pub fn with_alpha(self, alpha: f32) -> Color {
+        Color { a: alpha, ..self }
+    }
+
+    /// Mix between `self` at `t = 0` and `other` at `t = 1`.
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        Color {
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
+            a: self.a + (other.a - self.a) * t,
+        }
+    }
 }
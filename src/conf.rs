@@ -1,5 +1,42 @@
 use miniquad::conf::{Conf, Platform};
 
+use crate::error::Error;
+
+/// Width of the indices draw calls are made of.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum IndexFormat {
+    /// 32-bit when the backend supports them, 16-bit otherwise.
+    #[default]
+    Auto,
+    /// Limits draw calls to 65536 vertices, supported everywhere.
+    U16,
+    /// An `Error::InvalidConfig` on backends without 32-bit indices, such as WebGL 1 and
+    /// OpenGL ES 2.
+    U32,
+}
+
+impl IndexFormat {
+    /// The format used on a backend, `Auto` being decided by `supports_u32`.
+    pub(crate) fn resolve(self, supports_u32: bool) -> Result<IndexFormat, Error> {
+        match self {
+            IndexFormat::Auto if supports_u32 => Ok(IndexFormat::U32),
+            IndexFormat::Auto | IndexFormat::U16 => Ok(IndexFormat::U16),
+            IndexFormat::U32 if supports_u32 => Ok(IndexFormat::U32),
+            IndexFormat::U32 => Err(Error::InvalidConfig(
+                "32-bit indices are not supported by this backend".to_string(),
+            )),
+        }
+    }
+
+    /// Most vertices a single draw call can index, for a resolved format.
+    pub(crate) fn max_vertices(self) -> usize {
+        match self {
+            IndexFormat::U16 => u16::MAX as usize + 1,
+            IndexFormat::Auto | IndexFormat::U32 => u32::MAX as usize,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct WindowConfig {
     pub window_title: String,
@@ -8,9 +45,28 @@ pub struct WindowConfig {
     pub high_dpi: bool,
     pub fullscreen: bool,
     pub resizable: bool,
+    /// MSAA samples per pixel for the window, 1 disables multisampling.
+    pub sample_count: i32,
+    /// Initial value of `DrawContext::set_anti_aliasing`.
+    pub anti_aliasing: bool,
 
     pub max_vertices_per_draw: usize,
     pub max_indices_per_draw: usize,
+    /// 32-bit indices let `max_vertices_per_draw` go over 65536.
+    pub index_format: IndexFormat,
+
+    /// Run `update` this many times per second with a constant `dt`, whatever the frame rate.
+    /// `DrawContext::interpolation_alpha` tells how far each frame is past the last update.
+    pub fixed_update_rate: Option<f64>,
+    /// Longest time in seconds a frame accounts for, so a stall does not turn into a huge `dt`
+    /// or a burst of fixed updates. A quarter of a second by default, `None` never clamping.
+    pub max_dt: Option<f64>,
+    /// Wait between frames to run at most this many per second. Ignored on the web, where the
+    /// browser paces frames.
+    pub target_fps: Option<f64>,
+    /// Vertical blanks to wait for before presenting a frame, 0 disabling vsync.
+    /// Only a hint to the driver, left to the platform when `None`.
+    pub swap_interval: Option<i32>,
 }
 
 impl Default for WindowConfig {
@@ -22,13 +78,65 @@ impl Default for WindowConfig {
             high_dpi: false,
             fullscreen: false,
             resizable: false,
+            sample_count: 1,
+            anti_aliasing: false,
 
             max_vertices_per_draw: 10000,
-            max_indices_per_draw: 30000
+            max_indices_per_draw: 30000,
+            index_format: IndexFormat::Auto,
+
+            fixed_update_rate: None,
+            max_dt: Some(0.25),
+            target_fps: None,
+            swap_interval: None,
         }
     }
 }
 
+impl WindowConfig {
+    /// Check the values the renderer relies on, before any window is opened.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.window_width <= 0 || self.window_height <= 0 {
+            return Err(Error::InvalidConfig(format!(
+                "window size must be positive, got {} * {}",
+                self.window_width, self.window_height
+            )));
+        }
+        if self.sample_count < 1 {
+            return Err(Error::InvalidConfig(format!(
+                "sample_count must be at least 1, got {}",
+                self.sample_count
+            )));
+        }
+        // Draw calls are split to fit these limits, down to a single quad
+        if self.max_vertices_per_draw <= 4 || self.max_indices_per_draw <= 6 {
+            return Err(Error::InvalidConfig(format!(
+                "draws must fit at least a quad, over 4 vertices and 6 indices, got {} and {}",
+                self.max_vertices_per_draw, self.max_indices_per_draw
+            )));
+        }
+        for (name, value) in [
+            ("fixed_update_rate", self.fixed_update_rate),
+            ("max_dt", self.max_dt),
+            ("target_fps", self.target_fps),
+        ] {
+            if let Some(value) = value.filter(|value| !(value.is_finite() && *value > 0.)) {
+                return Err(Error::InvalidConfig(format!(
+                    "{} must be positive, got {}",
+                    name, value
+                )));
+            }
+        }
+        if let Some(swap_interval) = self.swap_interval.filter(|interval| *interval < 0) {
+            return Err(Error::InvalidConfig(format!(
+                "swap_interval cannot be negative, got {}",
+                swap_interval
+            )));
+        }
+        Ok(())
+    }
+}
+
 impl From<WindowConfig> for Conf {
     fn from(value: WindowConfig) -> Self {
         Self {
@@ -37,10 +145,13 @@ impl From<WindowConfig> for Conf {
             window_height: value.window_height,
             high_dpi: value.high_dpi,
             fullscreen: value.fullscreen,
-            sample_count: 1,
+            sample_count: value.sample_count,
             window_resizable: value.resizable,
             icon: None,
-            platform: Platform::default(),
+            platform: Platform {
+                swap_interval: value.swap_interval,
+                ..Default::default()
+            },
         }
     }
 }
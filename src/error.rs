@@ -0,0 +1,175 @@
+//! The error type of the fallible APIs, so an app can report startup and resource failures
+//! instead of aborting.
+//!
+//! Every `try_` method has a panicking counterpart, which panics with the `Display` of the error.
+
+use std::fmt;
+
+use miniquad::TextureFormat;
+
+#[cfg(feature = "text")]
+use crate::text::FontError;
+use crate::{image::ImageError, material::MaterialError};
+
+#[derive(Debug)]
+pub enum Error {
+    /// A built-in shader did not build, with the message of the graphics driver.
+    Shader(String),
+    /// A `WindowConfig` value the renderer cannot work with.
+    InvalidConfig(String),
+    /// The pixel buffer does not hold exactly `width` * `height` pixels of the texture format.
+    TextureBufferSize {
+        width: u16,
+        height: u16,
+        bytes_per_pixel: usize,
+        actual: usize,
+    },
+    /// An update reaches outside of the texture.
+    TextureOutOfBounds {
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        texture_width: u16,
+        texture_height: u16,
+    },
+    /// Textures can only be created from RGBA8, RGB8 or Alpha pixels.
+    UnsupportedTextureFormat(TextureFormat),
+    /// The texture is wider or taller than the backend allows.
+    TextureTooLarge {
+        width: u16,
+        height: u16,
+        max_size: u32,
+    },
+    /// Atlas images need at least one pixel.
+    EmptyAtlasImage {
+        width: u16,
+        height: u16,
+    },
+    /// A gradient was drawn without any color stop.
+    EmptyGradient,
+    /// Geometry submitted as one draw call is over the per-call limits.
+    DrawCallTooLarge {
+        vertices: usize,
+        indices: usize,
+        max_vertices: usize,
+        max_indices: usize,
+    },
+    /// A render target was drawn with while it is being drawn into.
+    RenderTargetDrawnIntoItself,
+    /// The material has no uniform with this name.
+    UnknownUniform(String),
+    /// A uniform was set to a value of another type than its initial one.
+    UniformTypeMismatch(String),
+    Image(ImageError),
+    Material(MaterialError),
+    #[cfg(feature = "text")]
+    Font(FontError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Shader(message) => write!(f, "Built-in shader failed to build: {}", message),
+            Error::InvalidConfig(message) => write!(f, "Invalid window config: {}", message),
+            Error::TextureBufferSize {
+                width,
+                height,
+                bytes_per_pixel,
+                actual,
+            } => write!(
+                f,
+                "Expected {} * {} * {} = {} bytes, got {} bytes in buffer",
+                width,
+                height,
+                bytes_per_pixel,
+                *width as usize * *height as usize * bytes_per_pixel,
+                actual
+            ),
+            Error::TextureOutOfBounds {
+                x,
+                y,
+                width,
+                height,
+                texture_width,
+                texture_height,
+            } => write!(
+                f,
+                "Area {} * {} at ({}, {}) is outside of the {} * {} texture",
+                width, height, x, y, texture_width, texture_height
+            ),
+            Error::UnsupportedTextureFormat(format) => {
+                write!(f, "{:?} textures cannot be created from pixels", format)
+            }
+            Error::TextureTooLarge {
+                width,
+                height,
+                max_size,
+            } => write!(
+                f,
+                "Texture of {} * {} is larger than the {} * {} the backend supports",
+                width, height, max_size, max_size
+            ),
+            Error::EmptyAtlasImage { width, height } => {
+                write!(
+                    f,
+                    "Atlas images cannot be empty, got {} * {}",
+                    width, height
+                )
+            }
+            Error::EmptyGradient => write!(f, "A gradient needs at least one color stop"),
+            Error::DrawCallTooLarge {
+                vertices,
+                indices,
+                max_vertices,
+                max_indices,
+            } => write!(
+                f,
+                "Draw call of {} vertices and {} indices is over the limits of {} and {}",
+                vertices, indices, max_vertices, max_indices
+            ),
+            Error::RenderTargetDrawnIntoItself => {
+                write!(f, "A render target cannot be drawn into itself")
+            }
+            Error::UnknownUniform(name) => write!(f, "Material has no uniform named {}", name),
+            Error::UniformTypeMismatch(name) => {
+                write!(f, "Uniform {} cannot change its type", name)
+            }
+            Error::Image(error) => error.fmt(f),
+            Error::Material(error) => error.fmt(f),
+            #[cfg(feature = "text")]
+            Error::Font(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Image(error) => Some(error),
+            Error::Material(error) => Some(error),
+            #[cfg(feature = "text")]
+            Error::Font(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ImageError> for Error {
+    fn from(error: ImageError) -> Self {
+        Error::Image(error)
+    }
+}
+
+impl From<MaterialError> for Error {
+    fn from(error: MaterialError) -> Self {
+        Error::Material(error)
+    }
+}
+
+#[cfg(feature = "text")]
+impl From<FontError> for Error {
+    fn from(error: FontError) -> Self {
+        Error::Font(error)
+    }
+}
@@ -0,0 +1,336 @@
+//! Multi-stop gradients for the `*_gradient` methods of `DrawContext`.
+//!
+//! Gradients are baked into vertex colors, so they batch with solid fills. Shapes are cut
+//! along the lines where the color stops being linear, then subdivided until interpolating
+//! between vertices matches the gradient to within a color step, or a shape runs out of splits.
+
+use std::f32::consts::PI;
+
+use glam::Vec2;
+
+use crate::{color::Color, error::Error};
+
+// Largest per-channel difference between the gradient and interpolated vertex colors
+const COLOR_TOLERANCE: f32 = 1. / 255.;
+// Keeps a pathological triangle from being split forever
+const MAX_SUBDIVISIONS: u8 = 16;
+// Bounds what a single shape costs every frame, later triangles banding once it is spent
+const MAX_SPLITS: usize = 1 << 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorStop {
+    /// Position along the gradient, from 0 to 1.
+    pub offset: f32,
+    pub color: Color,
+}
+
+impl ColorStop {
+    pub fn new(offset: f32, color: Color) -> Self {
+        Self { offset, color }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientKind {
+    /// Along the line from `start` to `end`.
+    Linear { start: Vec2, end: Vec2 },
+    /// Outwards from `center`, reaching the last stop at `radius`.
+    Radial { center: Vec2, radius: f32 },
+    /// Clockwise around `center`, starting `angle` degrees from the top.
+    Conic { center: Vec2, angle: f32 },
+}
+
+/// Positions use the same coordinates as the shape being filled, colors before the
+/// first stop and after the last one are those of the stops.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<ColorStop>,
+}
+
+impl Gradient {
+    pub fn linear(start: Vec2, end: Vec2, stops: &[ColorStop]) -> Self {
+        Self {
+            kind: GradientKind::Linear { start, end },
+            stops: stops.to_vec(),
+        }
+    }
+
+    pub fn radial(center: Vec2, radius: f32, stops: &[ColorStop]) -> Self {
+        Self {
+            kind: GradientKind::Radial { center, radius },
+            stops: stops.to_vec(),
+        }
+    }
+
+    pub fn conic(center: Vec2, angle: f32, stops: &[ColorStop]) -> Self {
+        Self {
+            kind: GradientKind::Conic { center, angle },
+            stops: stops.to_vec(),
+        }
+    }
+
+    /// Check that the gradient has the color stops drawing it needs.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.stops.is_empty() {
+            return Err(Error::EmptyGradient);
+        }
+        Ok(())
+    }
+}
+
+/// A gradient ready to color triangles, with its stops sorted.
+pub(crate) struct GradientShader {
+    kind: GradientKind,
+    stops: Vec<ColorStop>,
+    // Smallest edge worth subdividing, about a pixel
+    min_edge: f32,
+    // Splits this shape can still make, see `MAX_SPLITS`
+    splits_left: usize,
+}
+
+impl GradientShader {
+    pub(crate) fn try_new(gradient: &Gradient, min_edge: f32) -> Result<Self, Error> {
+        gradient.validate()?;
+        let mut stops = gradient.stops.clone();
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+
+        Ok(Self {
+            kind: gradient.kind,
+            stops,
+            min_edge,
+            splits_left: MAX_SPLITS,
+        })
+    }
+
+    /// Split `triangle` into smaller ones with their vertex colors, passed to `emit`.
+    pub(crate) fn shade(&mut self, triangle: [Vec2; 3], emit: &mut impl FnMut([(Vec2, Color); 3])) {
+        let mut pieces = vec![triangle.to_vec()];
+        for (point, normal) in self.cut_lines() {
+            pieces = pieces
+                .iter()
+                .flat_map(|piece| split(piece, point, normal))
+                .collect();
+        }
+
+        for piece in pieces {
+            // Conic angles are unwrapped around the middle of each piece, so pieces on either
+            // side of the starting angle get their own end of the gradient there
+            let reference = piece.iter().copied().sum::<Vec2>() / piece.len() as f32;
+            let reference = self.angle(reference, PI);
+            for i in 1..piece.len() - 1 {
+                self.subdivide(
+                    [piece[0], piece[i], piece[i + 1]],
+                    reference,
+                    MAX_SUBDIVISIONS,
+                    emit,
+                );
+            }
+        }
+    }
+
+    /// Lines, as a point and a normal, along which the color is not linear.
+    fn cut_lines(&self) -> Vec<(Vec2, Vec2)> {
+        match self.kind {
+            GradientKind::Linear { start, end } => {
+                let normal = (end - start).normalize_or_zero();
+                if normal == Vec2::ZERO {
+                    return Vec::new();
+                }
+
+                self.stops
+                    .iter()
+                    .map(|stop| (start + (end - start) * stop.offset, normal))
+                    .collect()
+            }
+            // Through the center, so every piece touches it and a ring inside a piece
+            // always crosses one of its edges
+            GradientKind::Radial { center, .. } => vec![(center, Vec2::X), (center, Vec2::Y)],
+            GradientKind::Conic { center, angle } => std::iter::once(0.)
+                .chain(self.stops.iter().map(|stop| stop.offset))
+                .map(|offset| {
+                    let direction = Vec2::from_angle((angle - 90.).to_radians() + offset * 2. * PI);
+                    (center, direction.perp())
+                })
+                .collect(),
+        }
+    }
+
+    /// Clockwise angle from the start of a conic gradient, in radians within half a turn of `reference`.
+    fn angle(&self, point: Vec2, reference: f32) -> f32 {
+        let GradientKind::Conic { center, angle } = self.kind else {
+            return 0.;
+        };
+        let offset = point - center;
+        if offset.length_squared() < f32::EPSILON {
+            return reference;
+        }
+
+        let angle = offset.to_angle() - (angle - 90.).to_radians();
+        angle - ((angle - reference) / (2. * PI)).round() * 2. * PI
+    }
+
+    fn color_at(&self, point: Vec2, reference: f32) -> Color {
+        let t = match self.kind {
+            GradientKind::Linear { start, end } => {
+                let axis = end - start;
+                (point - start).dot(axis) / axis.length_squared().max(f32::EPSILON)
+            }
+            GradientKind::Radial { center, radius } => {
+                point.distance(center) / radius.max(f32::EPSILON)
+            }
+            GradientKind::Conic { .. } => self.angle(point, reference) / (2. * PI),
+        };
+
+        let first = self.stops[0];
+        let last = self.stops[self.stops.len() - 1];
+        if t <= first.offset {
+            return first.color;
+        }
+        if t >= last.offset {
+            return last.color;
+        }
+
+        let next = self.stops.partition_point(|stop| stop.offset <= t);
+        let (from, to) = (self.stops[next - 1], self.stops[next]);
+        from.color
+            .lerp(to.color, (t - from.offset) / (to.offset - from.offset))
+    }
+
+    /// Whether the color halfway along an edge is too far from the mix of its ends.
+    ///
+    /// Only the edge itself is looked at, so triangles sharing it split it the same way
+    /// and no T-junctions appear between them.
+    fn needs_split(&self, a: Vec2, b: Vec2, reference: f32) -> bool {
+        if a.distance(b) <= self.min_edge {
+            return false;
+        }
+
+        // Radial colors can come back along an edge, the turning point being the closest
+        // one to the center
+        let closest = match self.kind {
+            GradientKind::Radial { center, .. } => {
+                let edge = b - a;
+                Some(((center - a).dot(edge) / edge.length_squared()).clamp(0., 1.))
+            }
+            _ => None,
+        };
+
+        let (from, to) = (self.color_at(a, reference), self.color_at(b, reference));
+        std::iter::once(0.5).chain(closest).any(|along| {
+            let expected: [f32; 4] = from.lerp(to, along).into();
+            let actual: [f32; 4] = self.color_at(a.lerp(b, along), reference).into();
+            expected
+                .iter()
+                .zip(actual)
+                .any(|(expected, actual)| (expected - actual).abs() > COLOR_TOLERANCE)
+        })
+    }
+
+    fn subdivide(
+        &mut self,
+        triangle: [Vec2; 3],
+        reference: f32,
+        depth: u8,
+        emit: &mut impl FnMut([(Vec2, Color); 3]),
+    ) {
+        let longest = (0..3)
+            .filter(|&i| {
+                depth > 0
+                    && self.splits_left > 0
+                    && self.needs_split(triangle[i], triangle[(i + 1) % 3], reference)
+            })
+            .max_by(|&i, &j| {
+                let length = |k: usize| triangle[k].distance_squared(triangle[(k + 1) % 3]);
+                length(i).total_cmp(&length(j))
+            });
+
+        let Some(i) = longest else {
+            emit(triangle.map(|point| (point, self.color_at(point, reference))));
+            return;
+        };
+
+        let (a, b, c) = (triangle[i], triangle[(i + 1) % 3], triangle[(i + 2) % 3]);
+        let middle = (a + b) / 2.;
+        self.splits_left -= 1;
+        self.subdivide([a, middle, c], reference, depth - 1, emit);
+        self.subdivide([middle, b, c], reference, depth - 1, emit);
+    }
+}
+
+/// Both sides of a convex polygon cut by the line through `point` with `normal`,
+/// leaving out sides with no area.
+fn split(polygon: &[Vec2], point: Vec2, normal: Vec2) -> Vec<Vec<Vec2>> {
+    let distances: Vec<f32> = polygon
+        .iter()
+        .map(|vertex| (*vertex - point).dot(normal))
+        .collect();
+    if distances.iter().all(|distance| *distance >= 0.)
+        || distances.iter().all(|distance| *distance <= 0.)
+    {
+        return vec![polygon.to_vec()];
+    }
+
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    for i in 0..polygon.len() {
+        let j = (i + 1) % polygon.len();
+        let (a, b) = (polygon[i], polygon[j]);
+        let (distance_a, distance_b) = (distances[i], distances[j]);
+
+        if distance_a >= 0. {
+            front.push(a);
+        }
+        if distance_a <= 0. {
+            back.push(a);
+        }
+        if distance_a * distance_b < 0. {
+            // Always from the same end, so the triangle on the other side of this edge
+            // gets exactly the same point
+            let (from, to, distance_from, distance_to) = if (a.x, a.y) < (b.x, b.y) {
+                (a, b, distance_a, distance_b)
+            } else {
+                (b, a, distance_b, distance_a)
+            };
+            let crossing = from + (to - from) * (distance_from / (distance_from - distance_to));
+            front.push(crossing);
+            back.push(crossing);
+        }
+    }
+
+    [front, back]
+        .into_iter()
+        .filter(|side| side.len() >= 3)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subdivision_is_capped_per_shape() {
+        // A thousand hard stops across a triangle far larger than a pixel
+        let stops: Vec<ColorStop> = (0..1000)
+            .map(|i| {
+                let gray = if i % 2 == 0 { 0 } else { 255 };
+                ColorStop::new(i as f32 / 1000., Color::from_rgba8(gray, gray, gray, 255))
+            })
+            .collect();
+        let gradient = Gradient::radial(Vec2::ZERO, 4000., &stops);
+        let mut shader = GradientShader::try_new(&gradient, 0.01).unwrap();
+
+        let mut triangles = 0;
+        shader.shade(
+            [
+                Vec2::new(-4000., -4000.),
+                Vec2::new(4000., -4000.),
+                Vec2::new(0., 4000.),
+            ],
+            &mut |_| triangles += 1,
+        );
+
+        // Each split adds one triangle to the few the cut lines leave
+        assert!(triangles <= MAX_SPLITS + 16, "{} triangles", triangles);
+    }
+}
@@ -0,0 +1,352 @@
+//! Decoding of encoded images for `TextureContext::register_texture_from_bytes`.
+//!
+//! Each format is behind the cargo feature of the same name, all on by default. Whatever
+//! the source, images come out as RGBA8 with straight alpha, top row first.
+
+use std::fmt;
+
+/// Encoded image formats, recognized from the first bytes of the data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Qoi,
+    Bmp,
+}
+
+impl ImageFormat {
+    /// The format `bytes` start with the signature of, if any.
+    pub fn sniff(bytes: &[u8]) -> Option<ImageFormat> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+            Some(ImageFormat::Jpeg)
+        } else if bytes.starts_with(b"qoif") {
+            Some(ImageFormat::Qoi)
+        } else if bytes.starts_with(b"BM") {
+            Some(ImageFormat::Bmp)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ImageError {
+    /// The data does not start with the signature of a known format.
+    UnknownFormat,
+    /// The cargo feature for this format is disabled.
+    Unsupported(ImageFormat),
+    /// The data is corrupted or uses a variant of the format that is not supported.
+    Decode {
+        format: ImageFormat,
+        message: String,
+    },
+    /// Textures are at most 65535 pixels wide and high.
+    TooLarge { width: u32, height: u32 },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::UnknownFormat => write!(f, "Image data is not in a known format"),
+            ImageError::Unsupported(format) => {
+                write!(f, "{:?} decoding is disabled by cargo features", format)
+            }
+            ImageError::Decode { format, message } => {
+                write!(f, "Cannot decode {:?} image: {}", format, message)
+            }
+            ImageError::TooLarge { width, height } => write!(
+                f,
+                "Image is {} * {} pixels, larger than the largest texture",
+                width, height
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// RGBA8 pixels of a decoded image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedImage {
+    pub width: u16,
+    pub height: u16,
+    pub pixels: Vec<u8>,
+}
+
+/// Decode `bytes`, sniffing the format from its signature.
+pub fn decode(bytes: &[u8]) -> Result<DecodedImage, ImageError> {
+    let format = ImageFormat::sniff(bytes).ok_or(ImageError::UnknownFormat)?;
+    let (width, height, pixels) = decode_format(format, bytes)
+        .ok_or(ImageError::Unsupported(format))?
+        .map_err(|message| ImageError::Decode { format, message })?;
+
+    match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(width), Ok(height)) => Ok(DecodedImage {
+            width,
+            height,
+            pixels,
+        }),
+        _ => Err(ImageError::TooLarge { width, height }),
+    }
+}
+
+/// Width, height and RGBA8 pixels, or why they could not be decoded.
+type Decoded = Result<(u32, u32, Vec<u8>), String>;
+
+/// `None` when the decoder for `format` is disabled.
+#[cfg_attr(
+    not(any(feature = "png", feature = "jpeg", feature = "qoi", feature = "bmp")),
+    allow(unused_variables)
+)]
+fn decode_format(format: ImageFormat, bytes: &[u8]) -> Option<Decoded> {
+    match format {
+        #[cfg(feature = "png")]
+        ImageFormat::Png => Some(decode_png(bytes)),
+        #[cfg(feature = "jpeg")]
+        ImageFormat::Jpeg => Some(decode_jpeg(bytes)),
+        #[cfg(feature = "qoi")]
+        ImageFormat::Qoi => Some(decode_qoi(bytes)),
+        #[cfg(feature = "bmp")]
+        ImageFormat::Bmp => Some(decode_bmp(bytes)),
+        #[cfg(not(all(feature = "png", feature = "jpeg", feature = "qoi", feature = "bmp")))]
+        _ => None,
+    }
+}
+
+#[cfg(feature = "png")]
+fn decode_png(bytes: &[u8]) -> Decoded {
+    let mut decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+    // Palettes expanded and 16 bit channels stripped, leaving 8 bit gray or RGB with or without alpha
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+
+    let mut buffer = vec![
+        0;
+        reader
+            .output_buffer_size()
+            .ok_or("Image dimensions must fit in memory")?
+    ];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(|err| err.to_string())?;
+    buffer.truncate(info.buffer_size());
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buffer,
+        png::ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        png::ColorType::Grayscale => buffer
+            .iter()
+            .flat_map(|gray| [*gray, *gray, *gray, 255])
+            .collect(),
+        png::ColorType::Indexed => return Err("Palette was not expanded".to_string()),
+    };
+
+    Ok((info.width, info.height, pixels))
+}
+
+#[cfg(feature = "jpeg")]
+fn decode_jpeg(bytes: &[u8]) -> Decoded {
+    let mut decoder = jpeg_decoder::Decoder::new(bytes);
+    let buffer = decoder.decode().map_err(|err| err.to_string())?;
+    let info = decoder.info().ok_or("Missing image header")?;
+
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => buffer
+            .chunks_exact(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::L8 => buffer
+            .iter()
+            .flat_map(|gray| [*gray, *gray, *gray, 255])
+            .collect(),
+        // Big endian, keeping the high byte
+        jpeg_decoder::PixelFormat::L16 => buffer
+            .chunks_exact(2)
+            .flat_map(|gray| [gray[0], gray[0], gray[0], 255])
+            .collect(),
+        // Adobe JPEGs store CMYK inverted
+        jpeg_decoder::PixelFormat::CMYK32 => buffer
+            .chunks_exact(4)
+            .flat_map(|pixel| {
+                let k = pixel[3] as u16;
+                [0, 1, 2]
+                    .map(|i| (pixel[i] as u16 * k / 255) as u8)
+                    .into_iter()
+                    .chain([255])
+            })
+            .collect(),
+    };
+
+    Ok((info.width as u32, info.height as u32, pixels))
+}
+
+#[cfg(feature = "qoi")]
+fn decode_qoi(bytes: &[u8]) -> Decoded {
+    let (header, buffer) = qoi::decode_to_vec(bytes).map_err(|err| err.to_string())?;
+
+    let pixels = match header.channels {
+        qoi::Channels::Rgba => buffer,
+        qoi::Channels::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+    };
+
+    Ok((header.width, header.height, pixels))
+}
+
+/// Uncompressed BMPs, from 1 to 32 bits per pixel, with bit field masks for 16 and 32 bits.
+#[cfg(feature = "bmp")]
+fn decode_bmp(bytes: &[u8]) -> Decoded {
+    const BI_RGB: u32 = 0;
+    const BI_BITFIELDS: u32 = 3;
+    const BI_ALPHABITFIELDS: u32 = 6;
+
+    let u16_at = |offset: usize| -> Result<u16, String> {
+        bytes
+            .get(offset..offset + 2)
+            .map(|field| u16::from_le_bytes([field[0], field[1]]))
+            .ok_or_else(|| "Unexpected end of data".to_string())
+    };
+    let u32_at = |offset: usize| -> Result<u32, String> {
+        bytes
+            .get(offset..offset + 4)
+            .map(|field| u32::from_le_bytes([field[0], field[1], field[2], field[3]]))
+            .ok_or_else(|| "Unexpected end of data".to_string())
+    };
+
+    let data_offset = u32_at(10)? as usize;
+    let header_size = u32_at(14)? as usize;
+    if header_size < 40 {
+        return Err("OS/2 bitmap headers are not supported".to_string());
+    }
+    let width = u32_at(18)? as i32;
+    let height = u32_at(22)? as i32;
+    let bits_per_pixel = u16_at(28)?;
+    let compression = u32_at(30)?;
+    let palette_size = match u32_at(46)? {
+        0 if bits_per_pixel <= 8 => 1 << bits_per_pixel,
+        count => count as usize,
+    };
+
+    if width <= 0 || height == 0 {
+        return Err(format!("Invalid dimensions {} * {}", width, height));
+    }
+    // Positive heights store the bottom row first
+    let bottom_up = height > 0;
+    let (width, height) = (width as usize, height.unsigned_abs() as usize);
+
+    // (red, green, blue, alpha) masks
+    let masks = match (compression, bits_per_pixel) {
+        (BI_RGB, 16) => [0x7c00, 0x03e0, 0x001f, 0],
+        (BI_RGB, 32) => [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000],
+        (BI_BITFIELDS | BI_ALPHABITFIELDS, 16 | 32) => {
+            // Right after a 40 bytes header unless the header includes them
+            let alpha = if header_size >= 56 || compression == BI_ALPHABITFIELDS {
+                u32_at(66)?
+            } else {
+                0
+            };
+            [u32_at(54)?, u32_at(58)?, u32_at(62)?, alpha]
+        }
+        (BI_RGB, 1 | 4 | 8 | 24) => [0; 4],
+        _ => {
+            return Err(format!(
+                "Compression {} with {} bits per pixel is not supported",
+                compression, bits_per_pixel
+            ));
+        }
+    };
+
+    // Sizes come from the header, so they are checked rather than trusted not to overflow
+    let palette_offset = header_size
+        .checked_add(14)
+        .ok_or("Header size out of range")?;
+    let palette: Vec<[u8; 4]> = (0..palette_size.min(256))
+        .map(|i| {
+            palette_offset
+                .checked_add(i * 4)
+                .and_then(|entry| bytes.get(entry..)?.get(..3))
+                .map(|bgr| [bgr[2], bgr[1], bgr[0], 255])
+                .ok_or_else(|| "Unexpected end of palette".to_string())
+        })
+        .collect::<Result<_, _>>()?;
+
+    // Rows are padded to multiples of 4 bytes
+    let row_size = width
+        .checked_mul(bits_per_pixel as usize)
+        .ok_or_else(|| format!("Dimensions {} * {} are too large", width, height))?
+        .div_ceil(32)
+        * 4;
+    let data_end = row_size
+        .checked_mul(height)
+        .and_then(|size| size.checked_add(data_offset))
+        .ok_or_else(|| format!("Dimensions {} * {} are too large", width, height))?;
+    let data = bytes
+        .get(data_offset..data_end)
+        .ok_or("Unexpected end of pixel data")?;
+
+    // Scaled in 64 bits, masks can be up to 32 bits wide
+    let channel = |value: u32, mask: u32| -> u8 {
+        if mask == 0 {
+            return 255;
+        }
+        let max = (mask >> mask.trailing_zeros()) as u64;
+        (((value & mask) >> mask.trailing_zeros()) as u64 * 255 / max) as u8
+    };
+
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        let row_index = if bottom_up { height - 1 - y } else { y };
+        let row = &data[row_index * row_size..(row_index + 1) * row_size];
+
+        for x in 0..width {
+            let pixel = match bits_per_pixel {
+                1 | 4 | 8 => {
+                    let bit = x * bits_per_pixel as usize;
+                    let index = (row[bit / 8] >> (8 - bits_per_pixel as usize - bit % 8))
+                        & ((1 << bits_per_pixel) - 1) as u8;
+                    *palette
+                        .get(index as usize)
+                        .ok_or("Palette index out of range")?
+                }
+                24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 255],
+                _ => {
+                    let value = if bits_per_pixel == 16 {
+                        u16::from_le_bytes([row[x * 2], row[x * 2 + 1]]) as u32
+                    } else {
+                        u32::from_le_bytes([
+                            row[x * 4],
+                            row[x * 4 + 1],
+                            row[x * 4 + 2],
+                            row[x * 4 + 3],
+                        ])
+                    };
+                    masks.map(|mask| channel(value, mask))
+                }
+            };
+            pixels.extend_from_slice(&pixel);
+        }
+    }
+
+    // 32 bit images commonly leave the unused byte at 0 rather than meaning fully transparent
+    if bits_per_pixel == 32
+        && compression == BI_RGB
+        && pixels.chunks_exact(4).all(|pixel| pixel[3] == 0)
+    {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel[3] = 255;
+        }
+    }
+
+    Ok((width as u32, height as u32, pixels))
+}
@@ -0,0 +1,63 @@
+//! User input forwarded from the window to `EventListener::input`.
+//!
+//! Pointer positions are in the same logical units as `DrawContext`, so they can be
+//! compared directly against drawn or laid out rectangles.
+
+use std::path::PathBuf;
+
+pub use miniquad::{KeyCode, KeyMods, MouseButton, TouchPhase};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    MouseMotion {
+        x: f32,
+        y: f32,
+    },
+    MouseButtonDown {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    },
+    MouseButtonUp {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    },
+    /// Scroll amount as reported by the platform, not scaled by DPI.
+    MouseWheel {
+        dx: f32,
+        dy: f32,
+    },
+    KeyDown {
+        keycode: KeyCode,
+        modifiers: KeyMods,
+        repeat: bool,
+    },
+    KeyUp {
+        keycode: KeyCode,
+        modifiers: KeyMods,
+    },
+    /// Text input, already translated through the keyboard layout.
+    Char {
+        character: char,
+        modifiers: KeyMods,
+        repeat: bool,
+    },
+    /// Touches also produce the left mouse button and motion events, like miniquad does.
+    Touch {
+        phase: TouchPhase,
+        id: u64,
+        x: f32,
+        y: f32,
+    },
+}
+
+/// A file dropped on the window, passed to `EventListener::files_dropped`.
+///
+/// Desktop platforms give both the path and the contents; in the browser only the contents
+/// are available.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DroppedFile {
+    pub path: Option<PathBuf>,
+    pub bytes: Option<Vec<u8>>,
+}
@@ -0,0 +1,552 @@
+//! Immediate mode layout, modeled on [Clay](https://github.com/nicbarker/clay).
+//!
+//! Every frame declares a tree of elements, then `Layout::end` sizes and positions them
+//! and draws their backgrounds into the `DrawContext`.
+//!
+//! Sizing runs one axis at a time: a bottom-up pass gives fit-content elements the size
+//! of their children, then a top-down pass hands the parent's leftover space to grow
+//! elements (or takes overflow back from fit and grow elements) and resolves percentages.
+
+use crate::{
+    Rect,
+    color::Color,
+    render::{CornerRadii, DrawContext},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SizingAxis {
+    Fixed(f32),
+    /// Wrap the children, clamped to `min..=max`.
+    Fit {
+        min: f32,
+        max: f32,
+    },
+    /// Start from the children size like `Fit`, then take a share of the free space in the parent.
+    Grow {
+        min: f32,
+        max: f32,
+    },
+    /// Fraction of the parent size left after its padding and child gaps, from 0 to 1.
+    Percent(f32),
+}
+
+impl SizingAxis {
+    pub fn fit() -> Self {
+        SizingAxis::Fit {
+            min: 0.,
+            max: f32::INFINITY,
+        }
+    }
+
+    pub fn grow() -> Self {
+        SizingAxis::Grow {
+            min: 0.,
+            max: f32::INFINITY,
+        }
+    }
+
+    fn bounds(&self) -> (f32, f32) {
+        match *self {
+            SizingAxis::Fixed(size) => (size, size),
+            SizingAxis::Fit { min, max } | SizingAxis::Grow { min, max } => (min, max),
+            SizingAxis::Percent(_) => (0., f32::INFINITY),
+        }
+    }
+}
+
+impl Default for SizingAxis {
+    fn default() -> Self {
+        SizingAxis::fit()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Sizing {
+    pub width: SizingAxis,
+    pub height: SizingAxis,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Padding {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl Padding {
+    pub fn all(padding: f32) -> Self {
+        Self {
+            left: padding,
+            right: padding,
+            top: padding,
+            bottom: padding,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LayoutDirection {
+    #[default]
+    LeftToRight,
+    TopToBottom,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AlignX {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AlignY {
+    #[default]
+    Top,
+    Center,
+    Bottom,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChildAlignment {
+    pub x: AlignX,
+    pub y: AlignY,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ElementConfig {
+    pub sizing: Sizing,
+    pub padding: Padding,
+    pub child_gap: f32,
+    pub direction: LayoutDirection,
+    pub child_alignment: ChildAlignment,
+    pub background: Option<Color>,
+    pub corner_radius: CornerRadii,
+    /// Clip the children to this element's bounds, e.g. for scrollable panels.
+    pub clip: bool,
+}
+
+/// Handle to a declared element, used to look up its rectangle once the layout has ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ElementId(usize);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    X,
+    Y,
+}
+
+struct Node {
+    config: ElementConfig,
+    children: Vec<usize>,
+    // [width, height] then [x, y], filled in by `Layout::end`
+    size: [f32; 2],
+    position: [f32; 2],
+}
+
+pub struct Layout {
+    // Parents are always declared before their children, so index order is a pre-order walk.
+    nodes: Vec<Node>,
+    open_elements: Vec<usize>,
+}
+
+pub struct ComputedLayout {
+    rects: Vec<Rect>,
+}
+
+impl Axis {
+    fn index(self) -> usize {
+        match self {
+            Axis::X => 0,
+            Axis::Y => 1,
+        }
+    }
+
+    fn sizing(self, sizing: &Sizing) -> SizingAxis {
+        match self {
+            Axis::X => sizing.width,
+            Axis::Y => sizing.height,
+        }
+    }
+
+    fn padding(self, padding: &Padding) -> (f32, f32) {
+        match self {
+            Axis::X => (padding.left, padding.right),
+            Axis::Y => (padding.top, padding.bottom),
+        }
+    }
+
+    fn is_main(self, direction: LayoutDirection) -> bool {
+        matches!(
+            (self, direction),
+            (Axis::X, LayoutDirection::LeftToRight) | (Axis::Y, LayoutDirection::TopToBottom)
+        )
+    }
+
+    /// Fraction of the free space placed before the children.
+    fn alignment(self, alignment: &ChildAlignment) -> f32 {
+        match self {
+            Axis::X => match alignment.x {
+                AlignX::Left => 0.,
+                AlignX::Center => 0.5,
+                AlignX::Right => 1.,
+            },
+            Axis::Y => match alignment.y {
+                AlignY::Top => 0.,
+                AlignY::Center => 0.5,
+                AlignY::Bottom => 1.,
+            },
+        }
+    }
+}
+
+impl Layout {
+    /// Begin a layout whose root element has a fixed `width` x `height`, usually the screen size.
+    pub fn new(width: f32, height: f32) -> Self {
+        Self::with_root(ElementConfig {
+            sizing: Sizing {
+                width: SizingAxis::Fixed(width),
+                height: SizingAxis::Fixed(height),
+            },
+            ..Default::default()
+        })
+    }
+
+    /// Begin a layout with a custom root element.
+    pub fn with_root(config: ElementConfig) -> Self {
+        Self {
+            nodes: vec![Node::new(config)],
+            open_elements: vec![0],
+        }
+    }
+
+    pub fn root(&self) -> ElementId {
+        ElementId(0)
+    }
+
+    /// Declare an element inside the currently open one, with `children` declaring its content.
+    pub fn element(
+        &mut self,
+        config: ElementConfig,
+        children: impl FnOnce(&mut Layout),
+    ) -> ElementId {
+        let index = self.nodes.len();
+        let parent = *self
+            .open_elements
+            .last()
+            .expect("The root element is open until the layout ends");
+
+        self.nodes.push(Node::new(config));
+        self.nodes[parent].children.push(index);
+
+        self.open_elements.push(index);
+        children(self);
+        self.open_elements.pop();
+
+        ElementId(index)
+    }
+
+    /// Declare an element without children.
+    pub fn leaf(&mut self, config: ElementConfig) -> ElementId {
+        self.element(config, |_| {})
+    }
+
+    /// Compute the final rectangles and draw element backgrounds, parents below children.
+    pub fn end(mut self, draw_context: &mut DrawContext) -> ComputedLayout {
+        let computed = self.compute();
+        self.draw_element(0, &computed, draw_context);
+
+        computed
+    }
+
+    fn draw_element(
+        &self,
+        index: usize,
+        computed: &ComputedLayout,
+        draw_context: &mut DrawContext,
+    ) {
+        let node = &self.nodes[index];
+        let rect = computed.rects[index];
+
+        if let Some(color) = node.config.background {
+            if node.config.corner_radius.is_zero() {
+                draw_context.draw_rect(rect.x, rect.y, rect.w, rect.h, color);
+            } else {
+                draw_context.draw_rounded_rect(rect, node.config.corner_radius, color);
+            }
+        }
+
+        if node.config.clip {
+            draw_context.push_clip_rect(rect.x, rect.y, rect.w, rect.h);
+        }
+        for child in &node.children {
+            self.draw_element(*child, computed, draw_context);
+        }
+        if node.config.clip {
+            draw_context.pop_clip_rect();
+        }
+    }
+
+    /// Compute the final rectangles without drawing anything.
+    pub fn compute(&mut self) -> ComputedLayout {
+        // Heights come after widths so that width-dependent content can be added later on.
+        self.fit_sizes(Axis::X);
+        self.distribute_sizes(Axis::X);
+        self.fit_sizes(Axis::Y);
+        self.distribute_sizes(Axis::Y);
+        self.position_children();
+
+        ComputedLayout {
+            rects: self
+                .nodes
+                .iter()
+                .map(|node| Rect {
+                    x: node.position[0],
+                    y: node.position[1],
+                    w: node.size[0],
+                    h: node.size[1],
+                })
+                .collect(),
+        }
+    }
+
+    fn fit_sizes(&mut self, axis: Axis) {
+        let a = axis.index();
+
+        // Reverse pre-order visits every child before its parent.
+        for index in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[index];
+            let (padding_start, padding_end) = axis.padding(&node.config.padding);
+
+            let children = node.children.iter().map(|child| self.nodes[*child].size[a]);
+            let content = if axis.is_main(node.config.direction) {
+                children.sum::<f32>() + gaps(node)
+            } else {
+                children.fold(0., f32::max)
+            } + padding_start
+                + padding_end;
+
+            let size = match axis.sizing(&node.config.sizing) {
+                SizingAxis::Fixed(size) => size,
+                SizingAxis::Fit { min, max } | SizingAxis::Grow { min, max } => {
+                    content.min(max).max(min)
+                }
+                // Resolved once the parent size is known
+                SizingAxis::Percent(_) => 0.,
+            };
+
+            self.nodes[index].size[a] = size;
+        }
+    }
+
+    fn distribute_sizes(&mut self, axis: Axis) {
+        let a = axis.index();
+
+        for index in 0..self.nodes.len() {
+            let node = &self.nodes[index];
+            let (padding_start, padding_end) = axis.padding(&node.config.padding);
+            let mut inner = node.size[a] - padding_start - padding_end;
+            let is_main = axis.is_main(node.config.direction);
+            if is_main {
+                inner -= gaps(node);
+            }
+            let children = node.children.clone();
+
+            for child in &children {
+                let child = &mut self.nodes[*child];
+                if let SizingAxis::Percent(percent) = axis.sizing(&child.config.sizing) {
+                    child.size[a] = inner.max(0.) * percent;
+                }
+            }
+
+            if is_main {
+                let used: f32 = children
+                    .iter()
+                    .map(|child| self.nodes[*child].size[a])
+                    .sum();
+                let free = inner - used;
+
+                if free > 0. {
+                    let growable: Vec<usize> = children
+                        .iter()
+                        .copied()
+                        .filter(|child| {
+                            matches!(
+                                axis.sizing(&self.nodes[*child].config.sizing),
+                                SizingAxis::Grow { .. }
+                            )
+                        })
+                        .collect();
+                    self.grow_evenly(axis, &growable, free);
+                } else if free < 0. {
+                    let shrinkable: Vec<usize> = children
+                        .iter()
+                        .copied()
+                        .filter(|child| is_shrinkable(axis, &self.nodes[*child]))
+                        .collect();
+                    self.shrink_evenly(axis, &shrinkable, -free);
+                }
+            } else {
+                for child in &children {
+                    let child = &mut self.nodes[*child];
+                    let (min, max) = axis.sizing(&child.config.sizing).bounds();
+
+                    match axis.sizing(&child.config.sizing) {
+                        SizingAxis::Grow { .. } if child.size[a] < inner => {
+                            child.size[a] = inner.min(max);
+                        }
+                        SizingAxis::Fit { .. } | SizingAxis::Grow { .. }
+                            if child.size[a] > inner =>
+                        {
+                            child.size[a] = inner.max(min);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /// Hand out `free` space so the smallest grow elements catch up with the larger ones first.
+    fn grow_evenly(&mut self, axis: Axis, children: &[usize], mut free: f32) {
+        let a = axis.index();
+        let mut children = children.to_vec();
+
+        while free > f32::EPSILON && !children.is_empty() {
+            let smallest = children
+                .iter()
+                .map(|child| self.nodes[*child].size[a])
+                .fold(f32::INFINITY, f32::min);
+            let next_smallest = children
+                .iter()
+                .map(|child| self.nodes[*child].size[a])
+                .filter(|size| *size > smallest)
+                .fold(f32::INFINITY, f32::min);
+            let tied = children
+                .iter()
+                .filter(|child| self.nodes[**child].size[a] == smallest)
+                .count();
+            let step = (next_smallest - smallest).min(free / tied as f32);
+
+            for child in &children {
+                let node = &mut self.nodes[*child];
+                if node.size[a] == smallest {
+                    let (_, max) = axis.sizing(&node.config.sizing).bounds();
+                    let grown = (node.size[a] + step).min(max);
+                    free -= grown - node.size[a];
+                    node.size[a] = grown;
+                }
+            }
+
+            children.retain(|child| {
+                let node = &self.nodes[*child];
+                node.size[a] < axis.sizing(&node.config.sizing).bounds().1
+            });
+        }
+    }
+
+    /// Take back `overflow` from the largest fit and grow elements first.
+    fn shrink_evenly(&mut self, axis: Axis, children: &[usize], mut overflow: f32) {
+        let a = axis.index();
+        let mut children = children.to_vec();
+
+        while overflow > f32::EPSILON && !children.is_empty() {
+            let largest = children
+                .iter()
+                .map(|child| self.nodes[*child].size[a])
+                .fold(f32::NEG_INFINITY, f32::max);
+            let next_largest = children
+                .iter()
+                .map(|child| self.nodes[*child].size[a])
+                .filter(|size| *size < largest)
+                .fold(f32::NEG_INFINITY, f32::max);
+            let tied = children
+                .iter()
+                .filter(|child| self.nodes[**child].size[a] == largest)
+                .count();
+            let step = (largest - next_largest).min(overflow / tied as f32);
+
+            for child in &children {
+                let node = &mut self.nodes[*child];
+                if node.size[a] == largest {
+                    let (min, _) = axis.sizing(&node.config.sizing).bounds();
+                    let shrunk = (node.size[a] - step).max(min);
+                    overflow -= node.size[a] - shrunk;
+                    node.size[a] = shrunk;
+                }
+            }
+
+            children.retain(|child| {
+                let node = &self.nodes[*child];
+                node.size[a] > axis.sizing(&node.config.sizing).bounds().0
+            });
+        }
+    }
+
+    fn position_children(&mut self) {
+        for index in 0..self.nodes.len() {
+            let node = &self.nodes[index];
+            let config = node.config;
+            let origin = node.position;
+            let size = node.size;
+            let children = node.children.clone();
+
+            for axis in [Axis::X, Axis::Y] {
+                let a = axis.index();
+                let (padding_start, padding_end) = axis.padding(&config.padding);
+                let inner = size[a] - padding_start - padding_end;
+                let alignment = axis.alignment(&config.child_alignment);
+
+                if axis.is_main(config.direction) {
+                    let content: f32 = children
+                        .iter()
+                        .map(|child| self.nodes[*child].size[a])
+                        .sum::<f32>()
+                        + gaps(&self.nodes[index]);
+                    let mut cursor = origin[a] + padding_start + (inner - content) * alignment;
+
+                    for child in &children {
+                        let child = &mut self.nodes[*child];
+                        child.position[a] = cursor;
+                        cursor += child.size[a] + config.child_gap;
+                    }
+                } else {
+                    for child in &children {
+                        let child = &mut self.nodes[*child];
+                        child.position[a] =
+                            origin[a] + padding_start + (inner - child.size[a]) * alignment;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Node {
+    fn new(config: ElementConfig) -> Self {
+        Self {
+            config,
+            children: Vec::new(),
+            size: [0., 0.],
+            position: [0., 0.],
+        }
+    }
+}
+
+impl ComputedLayout {
+    pub fn rect(&self, id: ElementId) -> Rect {
+        self.rects[id.0]
+    }
+}
+
+fn gaps(node: &Node) -> f32 {
+    node.children.len().saturating_sub(1) as f32 * node.config.child_gap
+}
+
+fn is_shrinkable(axis: Axis, node: &Node) -> bool {
+    matches!(
+        axis.sizing(&node.config.sizing),
+        SizingAxis::Fit { .. } | SizingAxis::Grow { .. }
+    )
+}
@@ -1,20 +1,86 @@
+use std::{cell::RefCell, rc::Rc};
+
 use crate::{
     conf::WindowConfig,
     render::{EventListener, RendererContext},
 };
 
+mod atlas;
+pub mod backend;
 pub mod color;
 pub mod conf;
+mod error;
+pub mod gradient;
+pub mod image;
+pub mod input;
+pub mod layout;
+pub mod material;
+pub mod path;
 pub mod render;
 mod shader;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+#[cfg(feature = "text")]
+pub mod text;
 pub mod texture;
+pub mod timing;
+
+pub use error::Error;
 
 pub struct Point {
     pub x: f32,
     pub y: f32
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+impl Rect {
+    /// Overlapping area of both rectangles, with a zero size when they do not overlap.
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+
+        Rect {
+            x,
+            y,
+            w: ((self.x + self.w).min(other.x + other.w) - x).max(0.),
+            h: ((self.y + self.h).min(other.y + other.h) - y).max(0.),
+        }
+    }
+
+    /// Whole pixels covered by this rectangle at `dpi_scale`, as (x, y, w, h) from the top-left.
+    pub(crate) fn to_pixels(self, dpi_scale: f32) -> (i32, i32, i32, i32) {
+        let x0 = (self.x * dpi_scale).round() as i32;
+        let y0 = (self.y * dpi_scale).round() as i32;
+        let x1 = ((self.x + self.w) * dpi_scale).round() as i32;
+        let y1 = ((self.y + self.h) * dpi_scale).round() as i32;
+
+        (x0, y0, x1 - x0, y1 - y0)
+    }
+}
+
 pub fn start<T: EventListener + 'static>(config: WindowConfig, user_state: T) {
+    if let Err(error) = try_start(config, user_state) {
+        panic!("{}", error);
+    }
+}
+
+/// Like `start`, returning startup failures such as an invalid config or shaders that do not build.
+///
+/// Failures after the window opened close it again and are returned once the event loop ends.
+/// On the web and iOS the event loop never returns, so only config errors are reported there.
+pub fn try_start<T: EventListener + 'static>(
+    config: WindowConfig,
+    user_state: T,
+) -> Result<(), Error> {
+    config.validate()?;
+
     let config_cloned = config.clone();
     let mut conf: miniquad::conf::Conf = config.into();
 
@@ -25,7 +91,30 @@ pub fn start<T: EventListener + 'static>(config: WindowConfig, user_state: T) {
         miniquad::conf::AppleGfxApi::OpenGl
     };
 
+    let startup_error = Rc::new(RefCell::new(None));
+    let startup_error_slot = startup_error.clone();
     miniquad::start(conf, move || {
-        Box::new(RendererContext::new(config_cloned, user_state))
+        match RendererContext::try_new(config_cloned, user_state) {
+            Ok(renderer) => Box::new(renderer),
+            Err(error) => {
+                *startup_error_slot.borrow_mut() = Some(error);
+                miniquad::window::order_quit();
+                Box::new(FailedStart)
+            }
+        }
     });
+
+    match startup_error.take() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Stands in for the renderer until the window closes after a startup failure.
+struct FailedStart;
+
+impl miniquad::EventHandler for FailedStart {
+    fn update(&mut self) {}
+
+    fn draw(&mut self) {}
 }
@@ -0,0 +1,134 @@
+//! User shader programs, created with `TextureContext::create_material` and drawn
+//! between `DrawContext::push_material` and `pop_material`.
+//!
+//! Material shaders get the same vertex attributes (`in_pos`, `in_color`, `in_texcoord`),
+//! `texture` sampler and `model` and `projection` uniforms as the built-in one, followed
+//! by their own uniforms.
+
+use std::{fmt, rc::Rc};
+
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use miniquad::UniformType;
+
+use crate::error::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UniformValue {
+    Float(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    Mat4(Mat4),
+}
+
+impl UniformValue {
+    pub(crate) fn uniform_type(&self) -> UniformType {
+        match self {
+            UniformValue::Float(_) => UniformType::Float1,
+            UniformValue::Vec2(_) => UniformType::Float2,
+            UniformValue::Vec3(_) => UniformType::Float3,
+            UniformValue::Vec4(_) => UniformType::Float4,
+            UniformValue::Mat4(_) => UniformType::Mat4,
+        }
+    }
+
+    /// Append the value as tightly packed floats, the layout uniforms are uploaded with.
+    pub(crate) fn write(&self, data: &mut Vec<f32>) {
+        match self {
+            UniformValue::Float(value) => data.push(*value),
+            UniformValue::Vec2(value) => data.extend(value.to_array()),
+            UniformValue::Vec3(value) => data.extend(value.to_array()),
+            UniformValue::Vec4(value) => data.extend(value.to_array()),
+            UniformValue::Mat4(value) => data.extend(value.to_cols_array()),
+        }
+    }
+}
+
+/// Shader sources of a material, only the ones for the running backend are compiled.
+pub struct MaterialParams<'a> {
+    /// GLSL 100 vertex shader, used on OpenGL.
+    pub vertex: &'a str,
+    /// GLSL 100 fragment shader, used on OpenGL.
+    pub fragment: &'a str,
+    /// Metal program with `vertexShader` and `fragmentShader` functions, used on macOS and iOS.
+    /// Uniforms are packed without padding, so its uniform struct should use packed types.
+    pub metal: &'a str,
+    /// Uniforms declared after `model` and `projection`, in order, with their initial values.
+    pub uniforms: &'a [(&'a str, UniformValue)],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialId(pub(crate) usize);
+
+/// A compiled material with its own uniform values.
+///
+/// Cloning it is how several draws share a shader with different uniforms; draws only batch
+/// together when both the material and its uniform values are the same.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    pub(crate) id: MaterialId,
+    names: Rc<[String]>,
+    pub(crate) values: Vec<UniformValue>,
+}
+
+impl Material {
+    pub(crate) fn new(id: MaterialId, uniforms: &[(&str, UniformValue)]) -> Self {
+        Self {
+            id,
+            names: uniforms.iter().map(|(name, _)| name.to_string()).collect(),
+            values: uniforms.iter().map(|(_, value)| *value).collect(),
+        }
+    }
+
+    pub fn id(&self) -> MaterialId {
+        self.id
+    }
+
+    pub fn uniform(&self, name: &str) -> Option<UniformValue> {
+        self.names
+            .iter()
+            .position(|uniform| uniform == name)
+            .map(|index| self.values[index])
+    }
+
+    /// Replace the value of the uniform `name`, which must keep its type.
+    pub fn set_uniform(&mut self, name: &str, value: UniformValue) {
+        self.try_set_uniform(name, value)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_set_uniform(&mut self, name: &str, value: UniformValue) -> Result<(), Error> {
+        let index = self
+            .names
+            .iter()
+            .position(|uniform| uniform == name)
+            .ok_or_else(|| Error::UnknownUniform(name.to_string()))?;
+
+        if std::mem::discriminant(&self.values[index]) != std::mem::discriminant(&value) {
+            return Err(Error::UniformTypeMismatch(name.to_string()));
+        }
+        self.values[index] = value;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct MaterialError {
+    message: String,
+}
+
+impl MaterialError {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for MaterialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Material shader failed to build: {}", self.message)
+    }
+}
+
+impl std::error::Error for MaterialError {}
@@ -0,0 +1,368 @@
+//! Outlines made of lines and curves, drawn with `DrawContext::draw_path` and `draw_path_stroke`.
+//!
+//! Curves are kept as-is until drawn, then flattened into line segments finely enough for
+//! the transform they are drawn with.
+
+use std::f32::consts::PI;
+
+use glam::Vec2;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FillRule {
+    /// Inside wherever the outline winds around a point, whatever the direction.
+    #[default]
+    NonZero,
+    /// Inside where a ray from a point crosses the outline an odd number of times,
+    /// so overlapping subpaths cut holes into each other.
+    EvenOdd,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Command {
+    MoveTo(Vec2),
+    LineTo(Vec2),
+    QuadTo(Vec2, Vec2),
+    CubicTo(Vec2, Vec2, Vec2),
+    Close,
+}
+
+/// A set of subpaths, built like a canvas path, e.g. `path.move_to(0., 0.).line_to(10., 0.).close()`.
+#[derive(Clone, Debug, Default)]
+pub struct Path {
+    commands: Vec<Command>,
+    current: Option<Vec2>,
+    // Where `close` goes back to
+    subpath_start: Vec2,
+}
+
+/// A subpath flattened into line segments.
+pub(crate) struct Polyline {
+    pub(crate) points: Vec<Vec2>,
+    pub(crate) closed: bool,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new subpath at (`x`, `y`).
+    pub fn move_to(&mut self, x: f32, y: f32) -> &mut Self {
+        let point = Vec2::new(x, y);
+        self.commands.push(Command::MoveTo(point));
+        self.current = Some(point);
+        self.subpath_start = point;
+        self
+    }
+
+    /// Straight line from the current point, or `move_to` when there is none.
+    pub fn line_to(&mut self, x: f32, y: f32) -> &mut Self {
+        if self.current.is_none() {
+            return self.move_to(x, y);
+        }
+
+        let point = Vec2::new(x, y);
+        self.commands.push(Command::LineTo(point));
+        self.current = Some(point);
+        self
+    }
+
+    /// Quadratic Bezier curve to (`x`, `y`) with the control point (`cx`, `cy`).
+    pub fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) -> &mut Self {
+        if self.current.is_none() {
+            self.move_to(cx, cy);
+        }
+
+        let point = Vec2::new(x, y);
+        self.commands
+            .push(Command::QuadTo(Vec2::new(cx, cy), point));
+        self.current = Some(point);
+        self
+    }
+
+    /// Cubic Bezier curve to (`x`, `y`) with the control points (`c1x`, `c1y`) and (`c2x`, `c2y`).
+    pub fn cubic_to(
+        &mut self,
+        c1x: f32,
+        c1y: f32,
+        c2x: f32,
+        c2y: f32,
+        x: f32,
+        y: f32,
+    ) -> &mut Self {
+        if self.current.is_none() {
+            self.move_to(c1x, c1y);
+        }
+
+        let point = Vec2::new(x, y);
+        self.commands.push(Command::CubicTo(
+            Vec2::new(c1x, c1y),
+            Vec2::new(c2x, c2y),
+            point,
+        ));
+        self.current = Some(point);
+        self
+    }
+
+    /// Arc of `radius` tangent to the line from the current point to (`x1`, `y1`) and to the line
+    /// from there to (`x2`, `y2`), joined to the current point by a straight line. This is how
+    /// corners are rounded, as with the canvas `arcTo`.
+    pub fn arc_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, radius: f32) -> &mut Self {
+        let Some(p0) = self.current else {
+            return self.move_to(x1, y1);
+        };
+        let p1 = Vec2::new(x1, y1);
+        let p2 = Vec2::new(x2, y2);
+
+        let d0 = (p0 - p1).normalize_or_zero();
+        let d1 = (p2 - p1).normalize_or_zero();
+        let angle = d0.angle_to(d1).abs();
+        if radius <= 0. || d0 == Vec2::ZERO || d1 == Vec2::ZERO || angle < 1e-4 || PI - angle < 1e-4
+        {
+            return self.line_to(x1, y1);
+        }
+
+        let tangent_distance = radius / (angle / 2.).tan();
+        let center = p1 + (d0 + d1).normalize() * radius / (angle / 2.).sin();
+        let start = p1 + d0 * tangent_distance;
+        let end = p1 + d1 * tangent_distance;
+
+        self.line_to(start.x, start.y);
+        self.arc_around(
+            center,
+            radius,
+            start - center,
+            (start - center).angle_to(end - center),
+        );
+        self
+    }
+
+    /// Straight line back to the start of the current subpath, which then ends.
+    pub fn close(&mut self) -> &mut Self {
+        if self.current.is_some() {
+            self.commands.push(Command::Close);
+            self.current = Some(self.subpath_start);
+        }
+        self
+    }
+
+    /// Append an arc around `center` as cubic curves of at most a quarter turn each.
+    fn arc_around(&mut self, center: Vec2, radius: f32, from: Vec2, sweep: f32) {
+        let segments = (sweep.abs() / (PI / 2.)).ceil().max(1.);
+        let step = sweep / segments;
+        // Control point distance of a cubic approximating an arc of `step` radians
+        let handle = 4. / 3. * (step / 4.).tan() * radius;
+
+        let mut direction = from.normalize();
+        for _ in 0..segments as usize {
+            let next = Vec2::from_angle(step).rotate(direction);
+            let start = center + direction * radius;
+            let end = center + next * radius;
+            let c1 = start + direction.perp() * handle;
+            let c2 = end - next.perp() * handle;
+
+            self.cubic_to(c1.x, c1.y, c2.x, c2.y, end.x, end.y);
+            direction = next;
+        }
+    }
+
+    /// Subpaths as polylines no further than `tolerance` from the curves.
+    pub(crate) fn flatten(&self, tolerance: f32) -> Vec<Polyline> {
+        let mut polylines = Vec::new();
+        let mut points: Vec<Vec2> = Vec::new();
+        let mut start = Vec2::ZERO;
+
+        let mut finish = |points: &mut Vec<Vec2>, closed| {
+            if points.len() > 1 {
+                polylines.push(Polyline {
+                    points: std::mem::take(points),
+                    closed,
+                });
+            }
+            points.clear();
+        };
+
+        for command in &self.commands {
+            match *command {
+                Command::MoveTo(point) => {
+                    finish(&mut points, false);
+                    points.push(point);
+                    start = point;
+                }
+                Command::LineTo(point) => {
+                    if points.is_empty() {
+                        points.push(start);
+                    }
+                    points.push(point);
+                }
+                Command::QuadTo(control, end) => {
+                    if points.is_empty() {
+                        points.push(start);
+                    }
+                    let from = points[points.len() - 1];
+                    // Largest flattening error is a quarter of the second difference over segments squared
+                    let segments = ((from - 2. * control + end).length() / (4. * tolerance))
+                        .sqrt()
+                        .ceil()
+                        .clamp(1., 256.) as usize;
+                    for i in 1..=segments {
+                        let t = i as f32 / segments as f32;
+                        let mt = 1. - t;
+                        points.push(mt * mt * from + 2. * mt * t * control + t * t * end);
+                    }
+                }
+                Command::CubicTo(c1, c2, end) => {
+                    if points.is_empty() {
+                        points.push(start);
+                    }
+                    let from = points[points.len() - 1];
+                    let second_difference = (from - 2. * c1 + c2)
+                        .length()
+                        .max((c1 - 2. * c2 + end).length());
+                    let segments = (3. * second_difference / (4. * tolerance))
+                        .sqrt()
+                        .ceil()
+                        .clamp(1., 256.) as usize;
+                    for i in 1..=segments {
+                        let t = i as f32 / segments as f32;
+                        let mt = 1. - t;
+                        points.push(
+                            mt * mt * mt * from
+                                + 3. * mt * mt * t * c1
+                                + 3. * mt * t * t * c2
+                                + t * t * t * end,
+                        );
+                    }
+                }
+                Command::Close => finish(&mut points, true),
+            }
+        }
+        finish(&mut points, false);
+
+        polylines
+    }
+}
+
+struct Edge {
+    top: Vec2,
+    bottom: Vec2,
+    // +1 when the outline goes down along this edge, -1 when it goes up
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f32) -> f32 {
+        self.top.x + (self.bottom.x - self.top.x) * (y - self.top.y) / (self.bottom.y - self.top.y)
+    }
+
+    /// Height at which both edges cross, strictly inside both of them.
+    fn crossing(&self, other: &Edge) -> Option<f32> {
+        let direction = self.bottom - self.top;
+        let other_direction = other.bottom - other.top;
+        let denominator = direction.perp_dot(other_direction);
+        if denominator.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let t = (other.top - self.top).perp_dot(other_direction) / denominator;
+        let u = (other.top - self.top).perp_dot(direction) / denominator;
+        if t > 0. && t < 1. && u > 0. && u < 1. {
+            Some(self.top.y + direction.y * t)
+        } else {
+            None
+        }
+    }
+}
+
+/// Split the inside of the closed `polylines` into trapezoids with horizontal top and bottom
+/// sides, as (top left, top right, bottom left, bottom right) corners.
+///
+/// The outline is cut into horizontal bands at every vertex and crossing, so edges never cross
+/// within a band and the inside of each band is a row of spans between consecutive edges.
+pub(crate) fn fill_trapezoids(polylines: &[Polyline], fill_rule: FillRule) -> Vec<[Vec2; 4]> {
+    let mut edges = Vec::new();
+    for polyline in polylines {
+        let count = polyline.points.len();
+        for i in 0..count {
+            let from = polyline.points[i];
+            let to = polyline.points[(i + 1) % count];
+            if from.y < to.y {
+                edges.push(Edge {
+                    top: from,
+                    bottom: to,
+                    winding: 1,
+                });
+            } else if from.y > to.y {
+                edges.push(Edge {
+                    top: to,
+                    bottom: from,
+                    winding: -1,
+                });
+            }
+        }
+    }
+    edges.sort_by(|a, b| a.top.y.total_cmp(&b.top.y));
+
+    let mut heights: Vec<f32> = edges
+        .iter()
+        .flat_map(|edge| [edge.top.y, edge.bottom.y])
+        .collect();
+    for (i, edge) in edges.iter().enumerate() {
+        for other in edges[i + 1..]
+            .iter()
+            .take_while(|other| other.top.y < edge.bottom.y)
+        {
+            heights.extend(edge.crossing(other));
+        }
+    }
+    heights.sort_by(f32::total_cmp);
+    heights.dedup();
+
+    let inside = |winding: i32| match fill_rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    };
+
+    let mut trapezoids = Vec::new();
+    // Edges spanning the current band, sorted by x. Edges never cross within a band, so the
+    // order carried over from the band above is only off where new edges were appended.
+    let mut active: Vec<(f32, &Edge)> = Vec::new();
+    let mut next_edge = 0;
+    for band in heights.windows(2) {
+        let (top, bottom) = (band[0], band[1]);
+        let middle = (top + bottom) / 2.;
+
+        while let Some(edge) = edges.get(next_edge).filter(|edge| edge.top.y <= top) {
+            active.push((0., edge));
+            next_edge += 1;
+        }
+        active.retain(|(_, edge)| edge.bottom.y >= bottom);
+        for (x, edge) in &mut active {
+            *x = edge.x_at(middle);
+        }
+        active.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut winding = 0;
+        let mut left = None;
+        for (_, edge) in &active {
+            let was_inside = inside(winding);
+            winding += edge.winding;
+
+            match (was_inside, inside(winding)) {
+                (false, true) => left = Some(*edge),
+                (true, false) => {
+                    let left = left.take().expect("A span must have been opened before");
+                    trapezoids.push([
+                        Vec2::new(left.x_at(top), top),
+                        Vec2::new(edge.x_at(top), top),
+                        Vec2::new(left.x_at(bottom), bottom),
+                        Vec2::new(edge.x_at(bottom), bottom),
+                    ]);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    trapezoids
+}
@@ -1,36 +1,214 @@
-use std::{cell::RefCell, f32::consts::PI, rc::Rc, vec};
+use std::{
+    cell::{Ref, RefCell},
+    f32::consts::PI,
+    rc::Rc,
+};
 
+#[cfg(feature = "text")]
+use crate::text::{FontId, GlyphCache, TextDimensions};
 use crate::{
-    Point,
+    Point, Rect,
+    backend::{Backend, GpuBackend},
     color::Color,
     conf::WindowConfig,
-    shader::{self, Uniforms},
-    texture::TextureContext,
-};
-use glam::{Affine2, Mat4, Vec2};
-use miniquad::{
-    Bindings, BufferLayout, EventHandler, Pipeline, PipelineParams, RenderingBackend, TextureId,
-    UniformsSource, VertexAttribute, window,
+    error::Error,
+    gradient::{Gradient, GradientShader},
+    input::{DroppedFile, InputEvent, KeyCode, KeyMods, MouseButton, TouchPhase},
+    material::Material,
+    path::{FillRule, Path, fill_trapezoids},
+    texture::{RenderTarget, TextureContext, TextureOptions},
+    timing::FrameTimer,
 };
+use glam::{Affine2, Vec2};
+use miniquad::{EventHandler, TextureFormat, TextureId};
 
 pub trait EventListener {
     fn update(&mut self, texture_context: &TextureContext, dt: f64);
     fn draw(&self, draw_context: &mut DrawContext);
+
+    /// Called for every input event, before the `update` of the frame it arrived in.
+    fn input(&mut self, _event: &InputEvent) {}
+
+    /// Called once when the renderer is created, before the first `update`.
+    fn init(&mut self, _texture_context: &TextureContext) {}
+
+    /// Called with the new screen size, in the same logical units as `DrawContext::screen_size`.
+    fn resize(&mut self, _width: f32, _height: f32) {}
+
+    /// miniquad has no focus events of its own, so focus is gained along with `restored`.
+    fn focus_gained(&mut self) {}
+
+    /// Called along with `minimized` while the window had focus.
+    fn focus_lost(&mut self) {}
+
+    /// Only reported on Android, X11 and in the browser, where X11 and the browser
+    /// also report it whenever the window loses focus.
+    fn minimized(&mut self) {}
+
+    /// Only reported on Android, X11 and in the browser, like `minimized`.
+    fn restored(&mut self) {}
+
+    /// Called when the window is asked to close. Returning `false` keeps it open.
+    fn quit_requested(&mut self) -> bool {
+        true
+    }
+
+    fn files_dropped(&mut self, _files: &[DroppedFile]) {}
 }
 
+#[derive(Clone, Copy)]
 #[repr(C)]
 pub struct Vertex {
-    pos: [f32; 2],
-    color: [f32; 4],
-    tex_coord: [f32; 2],
+    pub(crate) pos: [f32; 2],
+    pub(crate) color: [f32; 4],
+    pub(crate) tex_coord: [f32; 2],
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct TextureArea {
     pub texture: TextureId,
     pub location: Vec2,
     pub size: Vec2,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CornerRadii {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32,
+}
+
+impl CornerRadii {
+    pub fn all(radius: f32) -> Self {
+        Self {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        }
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        *self == CornerRadii::default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BorderWidths {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl BorderWidths {
+    pub fn all(width: f32) -> Self {
+        Self {
+            left: width,
+            right: width,
+            top: width,
+            bottom: width,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    /// Extends past the end points by half the thickness.
+    Square,
+}
+
+/// How a draw is combined with the pixels below it, colors going from 0 to 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Straight alpha "over", what `Color` and textures are meant for.
+    #[default]
+    Alpha,
+    /// "Over" for colors and textures whose RGB are already multiplied by their alpha.
+    PremultipliedAlpha,
+    /// Adds the color, weighted by its alpha, to what is below.
+    Additive,
+    /// Darkens what is below by multiplying it with the color.
+    Multiply,
+    /// Lightens what is below, the inverse of multiplying the inverted colors.
+    Screen,
+    /// Overwrites what is below, alpha included.
+    Replace,
+}
+
+impl BlendMode {
+    /// Multiply and screen only have blend equations for premultiplied colors, so vertex colors
+    /// are premultiplied when recorded; textures drawn with them should be premultiplied too.
+    pub(crate) fn premultiplies_colors(self) -> bool {
+        matches!(self, BlendMode::Multiply | BlendMode::Screen)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeStyle {
+    pub thickness: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Longest miter, as a multiple of half the thickness, before falling back to a bevel.
+    pub miter_limit: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            thickness: 1.,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: 4.,
+        }
+    }
+}
+
+/// How the edges and center of a nine-patch fill the space between its corners.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NinePatchMode {
+    #[default]
+    Stretch,
+    /// Repeat at the drawn size of the image, the last tile being cut off.
+    Tile,
+}
+
+/// Splits an image into corners drawn at a fixed size, edges and a center filling the rest.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NinePatch {
+    /// Size of the texture area in pixels.
+    pub image_size: Vec2,
+    /// Width of each border in image pixels.
+    pub insets: BorderWidths,
+    /// Logical units per image pixel, for the corners and the tiles.
+    pub scale: f32,
+    pub mode: NinePatchMode,
+}
+
+impl NinePatch {
+    /// Stretched edges and center, one logical unit per image pixel.
+    pub fn new(image_width: f32, image_height: f32, insets: BorderWidths) -> Self {
+        Self {
+            image_size: Vec2::new(image_width, image_height),
+            insets,
+            scale: 1.,
+            mode: NinePatchMode::Stretch,
+        }
+    }
+}
+
 impl Vertex {
     pub fn new(x: f32, y: f32, u: f32, v: f32, color: Color) -> Vertex {
         Vertex {
@@ -42,56 +220,82 @@ impl Vertex {
 }
 
 #[derive(Default)]
-struct VecSlice {
-    offset: usize,
-    length: usize,
+pub(crate) struct VecSlice {
+    pub(crate) offset: usize,
+    pub(crate) length: usize,
 }
 
-struct DrawCall {
-    vertex_indices_slice: VecSlice,
-    index_indices_slice: VecSlice,
+pub(crate) struct DrawCall {
+    pub(crate) vertex_indices_slice: VecSlice,
+    pub(crate) index_indices_slice: VecSlice,
+
+    pub(crate) texture: TextureId,
+    /// Logical-unit scissor rectangle, `None` when drawing is not clipped.
+    pub(crate) clip: Option<Rect>,
+    pub(crate) shading: Shading,
+    /// User shader replacing the built-in one for `Shading::Textured` calls.
+    pub(crate) material: Option<Material>,
+    pub(crate) blend_mode: BlendMode,
+    /// Where the call draws to, `None` for the screen.
+    pub(crate) target: Option<RenderTarget>,
+    /// Color the whole target is filled with before drawing.
+    pub(crate) clear: Option<Color>,
+}
+
+/// A render target being drawn into, with the clip and transform stacks of what is below it.
+struct PushedTarget {
+    target: RenderTarget,
+    clip_stack: Vec<Rect>,
+    transform_stack: Vec<Affine2>,
+}
 
-    texture: TextureId,
+/// Fragment shader a draw call is rendered with, along with its parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Shading {
+    /// Vertex color times the texture.
+    Textured,
+    /// Vertex color faded out by a blurred rounded box, `tex_coord` being the offset
+    /// from the center of the box.
+    BoxShadow {
+        half_size: Vec2,
+        corner_radius: f32,
+        sigma: f32,
+    },
 }
 
 pub struct DrawContext {
-    vertex_buffer: Vec<Vertex>,
-    index_buffer: Vec<u16>,
-    draw_call_vec: Vec<DrawCall>,
+    pub(crate) vertex_buffer: Vec<Vertex>,
+    pub(crate) index_buffer: Vec<u32>,
+    pub(crate) draw_call_vec: Vec<DrawCall>,
     default_texture: TextureId,
+    screen_size: (f32, f32),
+    dpi_scale: f32,
+    interpolation_alpha: f32,
+    anti_aliasing: bool,
+    // Each entry is already intersected with the ones below it
+    clip_stack: Vec<Rect>,
+    // Each entry is already composed with the ones below it
+    transform_stack: Vec<Affine2>,
+    target_stack: Vec<PushedTarget>,
+    material_stack: Vec<Material>,
+    blend_mode_stack: Vec<BlendMode>,
+    #[cfg(feature = "text")]
+    glyph_cache: Rc<RefCell<GlyphCache>>,
 
     max_vertex_per_call: usize,
     max_index_per_call: usize,
 }
 
-pub struct RendererContext<T> {
+pub struct RendererContext<T, B = GpuBackend> {
     draw_context: DrawContext,
     texture_context: TextureContext,
 
-    last_update_time: f64,
+    frame_timer: FrameTimer,
+    focused: bool,
 
     app_listener: T,
 
-    pipeline: Pipeline,
-    bindings: Bindings,
-    uniform: Uniforms,
-    backend: Rc<RefCell<Box<dyn RenderingBackend>>>,
-}
-
-impl DrawCall {
-    fn new(texture: TextureId, vertex_offset: usize, index_offset: usize) -> Self {
-        Self {
-            texture,
-            vertex_indices_slice: VecSlice {
-                offset: vertex_offset,
-                length: 0,
-            },
-            index_indices_slice: VecSlice {
-                offset: index_offset,
-                length: 0,
-            },
-        }
-    }
+    backend: Rc<RefCell<B>>,
 }
 
 // [FIXME]: Color is being moved by call when not needed. Switch to reference taking instead.
@@ -101,6 +305,8 @@ impl DrawContext {
         default_texture: TextureId,
         max_vertex_per_call: usize,
         max_index_per_call: usize,
+        anti_aliasing: bool,
+        #[cfg(feature = "text")] glyph_cache: Rc<RefCell<GlyphCache>>,
     ) -> Self {
         Self {
             // Pre-allocate for 5k vertices; This can be extended, the limit is per draw-call.
@@ -110,35 +316,124 @@ impl DrawContext {
             max_vertex_per_call,
             max_index_per_call,
             default_texture,
+            screen_size: (0., 0.),
+            dpi_scale: 1.,
+            interpolation_alpha: 1.,
+            anti_aliasing,
+            clip_stack: Vec::new(),
+            transform_stack: Vec::new(),
+            target_stack: Vec::new(),
+            material_stack: Vec::new(),
+            blend_mode_stack: Vec::new(),
+            #[cfg(feature = "text")]
+            glyph_cache,
+        }
+    }
+
+    /// An empty draw call starting at the end of the buffers, with the current clip, blend mode and target.
+    fn new_draw_call(
+        &self,
+        texture: TextureId,
+        shading: Shading,
+        material: Option<Material>,
+    ) -> DrawCall {
+        DrawCall {
+            texture,
+            clip: self.clip_stack.last().copied(),
+            shading,
+            material,
+            blend_mode: self.blend_mode(),
+            target: self.target_stack.last().map(|pushed| pushed.target),
+            clear: None,
+            vertex_indices_slice: VecSlice {
+                offset: self.vertex_buffer.len(),
+                length: 0,
+            },
+            index_indices_slice: VecSlice {
+                offset: self.index_buffer.len(),
+                length: 0,
+            },
         }
     }
 
-    fn create_draw_call(&mut self, vertices: Box<[Vertex]>, indices: &[u16], texture: TextureId) {
-        // throw if a single call is too large
-        // [TODO] Since the lib's draw call will never exceed this size, move the check to user-supplied vertices
-        assert!(vertices.len() < self.max_vertex_per_call);
-        assert!(indices.len() < self.max_index_per_call);
-
-        match self.draw_call_vec.last() {
-            Some(draw_call) => {
-                if draw_call.texture != texture
-                    || draw_call.vertex_indices_slice.length + vertices.len()
-                        > self.max_vertex_per_call
-                    || draw_call.index_indices_slice.length + indices.len()
-                        > self.max_index_per_call
-                {
-                    self.draw_call_vec.push(DrawCall::new(
-                        texture,
-                        self.vertex_buffer.len(),
-                        self.index_buffer.len(),
-                    ));
+    fn create_draw_call(&mut self, vertices: Box<[Vertex]>, indices: &[u32], texture: TextureId) {
+        self.try_create_draw_call(vertices, indices, texture)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    fn try_create_draw_call(
+        &mut self,
+        vertices: Box<[Vertex]>,
+        indices: &[u32],
+        texture: TextureId,
+    ) -> Result<(), Error> {
+        self.try_create_shaded_draw_call(vertices, indices, texture, Shading::Textured)
+    }
+
+    /// Add the geometry to the current draw call, or to a new one when it cannot batch with it.
+    /// Nothing is added on error.
+    fn try_create_shaded_draw_call(
+        &mut self,
+        mut vertices: Box<[Vertex]>,
+        indices: &[u32],
+        texture: TextureId,
+        shading: Shading,
+    ) -> Result<(), Error> {
+        // Not reached from the drawing methods: larger geometry goes through `draw_mesh`, which
+        // splits it, and `WindowConfig::validate` leaves room for at least a quad.
+        if vertices.len() >= self.max_vertex_per_call || indices.len() >= self.max_index_per_call {
+            return Err(Error::DrawCallTooLarge {
+                vertices: vertices.len(),
+                indices: indices.len(),
+                max_vertices: self.max_vertex_per_call,
+                max_indices: self.max_index_per_call,
+            });
+        }
+        let target = self.target_stack.last().map(|pushed| pushed.target);
+        if target.is_some_and(|target| target.texture == texture) {
+            return Err(Error::RenderTargetDrawnIntoItself);
+        }
+
+        // Transforms are applied on the CPU rather than through the `model` uniform,
+        // so differently transformed shapes still share a draw call.
+        if let Some(transform) = self.transform_stack.last() {
+            for vertex in vertices.iter_mut() {
+                vertex.pos = transform.transform_point2(Vec2::from(vertex.pos)).into();
+            }
+        }
+
+        let blend_mode = self.blend_mode();
+        if blend_mode.premultiplies_colors() {
+            for vertex in vertices.iter_mut() {
+                let alpha = vertex.color[3];
+                for channel in &mut vertex.color[..3] {
+                    *channel *= alpha;
                 }
             }
-            None => self.draw_call_vec.push(DrawCall::new(
-                texture,
-                self.vertex_buffer.len(),
-                self.index_buffer.len(),
-            )),
+        }
+
+        let clip = self.clip_stack.last().copied();
+
+        // Materials only replace the built-in shader, other shadings keep their own
+        let material = match shading {
+            Shading::Textured => self.material_stack.last(),
+            Shading::BoxShadow { .. } => None,
+        };
+
+        let batched = self.draw_call_vec.last().is_some_and(|draw_call| {
+            draw_call.texture == texture
+                && draw_call.clip == clip
+                && draw_call.shading == shading
+                && draw_call.material.as_ref() == material
+                && draw_call.blend_mode == blend_mode
+                && draw_call.target == target
+                && draw_call.vertex_indices_slice.length + vertices.len()
+                    <= self.max_vertex_per_call
+                && draw_call.index_indices_slice.length + indices.len() <= self.max_index_per_call
+        });
+        if !batched {
+            self.draw_call_vec
+                .push(self.new_draw_call(texture, shading, material.cloned()));
         }
 
         let current_draw_call = self
@@ -149,16 +444,189 @@ impl DrawContext {
         self.index_buffer.extend(
             indices
                 .iter()
-                .map(|index| index + current_draw_call.vertex_indices_slice.length as u16),
+                .map(|index| index + current_draw_call.vertex_indices_slice.length as u32),
         );
         current_draw_call.vertex_indices_slice.length += vertices.len();
         self.vertex_buffer.extend(vertices);
+        Ok(())
+    }
+
+    /// Size of the drawable area in logical units, e.g. to size a `Layout` root.
+    pub fn screen_size(&self) -> (f32, f32) {
+        self.screen_size
+    }
+
+    /// How far this frame is between the last fixed update and the next one, from 0 to 1,
+    /// to draw state interpolated from the previous update to the last one.
+    /// Always 1 without `WindowConfig::fixed_update_rate`.
+    pub fn interpolation_alpha(&self) -> f32 {
+        self.interpolation_alpha
+    }
+
+    /// Feather the edges of circles, polygons, arcs, rotated rectangles and lines over one pixel.
+    /// Defaults to `WindowConfig::anti_aliasing`.
+    pub fn set_anti_aliasing(&mut self, enabled: bool) {
+        self.anti_aliasing = enabled;
+    }
+
+    pub fn anti_aliasing(&self) -> bool {
+        self.anti_aliasing
+    }
+
+    /// Fill a star-shaped outline, as seen from its first point, with a fading one pixel fringe.
+    fn fill_anti_aliased(&mut self, outline: &[Vec2], color: Color) {
+        let mut mesh = Mesh::default();
+        mesh.fill_anti_aliased(outline, color, self.pixel_size());
+
+        self.draw_mesh(mesh);
+    }
+
+    /// Submit `mesh` with the default texture, split into several calls when it is over the per-call limits.
+    fn draw_mesh(&mut self, mesh: Mesh) {
+        if mesh.vertices.len() < self.max_vertex_per_call
+            && mesh.indices.len() < self.max_index_per_call
+        {
+            self.create_draw_call(
+                mesh.vertices.into_boxed_slice(),
+                &mesh.indices,
+                self.default_texture,
+            );
+            return;
+        }
+
+        // Position of each mesh vertex in the current chunk, u32::MAX when not in it yet
+        let mut remap = vec![u32::MAX; mesh.vertices.len()];
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for triangle in mesh.indices.chunks_exact(3) {
+            if vertices.len() + 3 >= self.max_vertex_per_call
+                || indices.len() + 3 >= self.max_index_per_call
+            {
+                self.create_draw_call(
+                    std::mem::take(&mut vertices).into_boxed_slice(),
+                    &indices,
+                    self.default_texture,
+                );
+                indices.clear();
+                remap.fill(u32::MAX);
+            }
+
+            for &index in triangle {
+                if remap[index as usize] == u32::MAX {
+                    remap[index as usize] = vertices.len() as u32;
+                    vertices.push(mesh.vertices[index as usize]);
+                }
+                indices.push(remap[index as usize]);
+            }
+        }
+
+        self.create_draw_call(vertices.into_boxed_slice(), &indices, self.default_texture);
     }
 
     pub fn clear(&mut self) {
         self.vertex_buffer.clear();
         self.index_buffer.clear();
         self.draw_call_vec.clear();
+        self.clip_stack.clear();
+        self.transform_stack.clear();
+        self.target_stack.clear();
+        self.material_stack.clear();
+        self.blend_mode_stack.clear();
+    }
+
+    /// Apply `transform` to everything drawn until the matching `pop_transform`,
+    /// after the transforms already pushed, e.g. `Affine2::from_translation` to move
+    /// a whole group. Clip rectangles stay in screen space.
+    pub fn push_transform(&mut self, transform: Affine2) {
+        let parent = self
+            .transform_stack
+            .last()
+            .copied()
+            .unwrap_or(Affine2::IDENTITY);
+
+        self.transform_stack.push(parent * transform);
+    }
+
+    pub fn pop_transform(&mut self) {
+        assert!(
+            self.transform_stack.pop().is_some(),
+            "pop_transform called without a matching push_transform"
+        );
+    }
+
+    /// Draw into `target` instead of the screen until the matching `pop_render_target`, in its own
+    /// pixel coordinates and without the clip rectangles and transforms pushed so far. The target
+    /// is first filled with `clear` if set, otherwise drawing goes over what it already holds.
+    pub fn push_render_target(&mut self, target: RenderTarget, clear: Option<Color>) {
+        self.target_stack.push(PushedTarget {
+            target,
+            clip_stack: std::mem::take(&mut self.clip_stack),
+            transform_stack: std::mem::take(&mut self.transform_stack),
+        });
+
+        if clear.is_some() {
+            let mut draw_call = self.new_draw_call(self.default_texture, Shading::Textured, None);
+            draw_call.clear = clear;
+            self.draw_call_vec.push(draw_call);
+        }
+    }
+
+    pub fn pop_render_target(&mut self) {
+        let pushed = self
+            .target_stack
+            .pop()
+            .expect("pop_render_target called without a matching push_render_target");
+
+        self.clip_stack = pushed.clip_stack;
+        self.transform_stack = pushed.transform_stack;
+    }
+
+    /// Draw with `material` instead of the built-in shader until the matching `pop_material`.
+    /// Its uniform values are copied, so changing them afterwards only affects later pushes.
+    pub fn push_material(&mut self, material: &Material) {
+        self.material_stack.push(material.clone());
+    }
+
+    pub fn pop_material(&mut self) {
+        assert!(
+            self.material_stack.pop().is_some(),
+            "pop_material called without a matching push_material"
+        );
+    }
+
+    /// Blend everything drawn until the matching `pop_blend_mode` with `blend_mode`.
+    pub fn push_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode_stack.push(blend_mode);
+    }
+
+    pub fn pop_blend_mode(&mut self) {
+        assert!(
+            self.blend_mode_stack.pop().is_some(),
+            "pop_blend_mode called without a matching push_blend_mode"
+        );
+    }
+
+    /// How draws are currently combined with what is below them, `BlendMode::Alpha` by default.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode_stack.last().copied().unwrap_or_default()
+    }
+
+    /// Clip everything drawn until the matching `pop_clip_rect` to this rectangle,
+    /// intersected with the clip rectangles already pushed.
+    pub fn push_clip_rect(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        let mut clip = Rect { x, y, w, h };
+        if let Some(parent) = self.clip_stack.last() {
+            clip = clip.intersection(parent);
+        }
+
+        self.clip_stack.push(clip);
+    }
+
+    pub fn pop_clip_rect(&mut self) {
+        assert!(
+            self.clip_stack.pop().is_some(),
+            "pop_clip_rect called without a matching push_clip_rect"
+        );
     }
 
     pub fn draw_rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
@@ -169,7 +637,7 @@ impl DrawContext {
             Vertex::new(x + w,     y, 0., 0., color),
             Vertex::new(x + w, y + h, 0., 0., color)
         ]);
-        let indices: [u16; 6] = [0, 1, 3, 0, 3, 2];
+        let indices: [u32; 6] = [0, 1, 3, 0, 3, 2];
 
         self.create_draw_call(vertices, &indices, self.default_texture);
     }
@@ -191,7 +659,14 @@ impl DrawContext {
         })
         .collect::<Vec<Vertex>>()
         .into_boxed_slice();
-        let indices: [u16; 6] = [0, 1, 3, 0, 3, 2];
+        let indices: [u32; 6] = [0, 1, 3, 0, 3, 2];
+
+        if self.anti_aliasing {
+            // Corners in outline order rather than strip order
+            let outline = [0, 2, 3, 1].map(|i| Vec2::from(vertices[i].pos));
+            self.fill_anti_aliased(&outline, color);
+            return;
+        }
 
         self.create_draw_call(vertices, &indices, self.default_texture);
     }
@@ -207,32 +682,40 @@ impl DrawContext {
         let begin_angle = (begin_angle - 90.).to_radians();
         let arc_size = arc_size.clamp(0., 360.).to_radians();
 
-        // A good-enough circle approx is a 36-sided regular polygon
-        // So we can scale down the amount of sides needed based on the arc_size to save vertices.
-
-        let sides = (36. * (arc_size / (2. * PI))).ceil() as u8;
+        let sides = arc_sides(arc_size);
+
+        if self.anti_aliasing {
+            // The pivot comes first so the fill fans out from it, arcs over 180 degrees are not convex
+            let outline: Vec<Vec2> = std::iter::once(Vec2::new(pivot.x, pivot.y))
+                .chain((0..=sides).map(|i| {
+                    let angle = i as f32 / sides as f32 * arc_size + begin_angle;
+                    Vec2::new(pivot.x, pivot.y) + radius * Vec2::new(angle.cos(), angle.sin())
+                }))
+                .collect();
+            self.fill_anti_aliased(&outline, color);
+            return;
+        }
 
-        let mut vertices: Vec<Vertex> = Vec::with_capacity((sides + 1) as usize);
-        let mut indices: Vec<u16> = Vec::with_capacity((sides * 3) as usize);
+        let mut vertices: Vec<Vertex> = Vec::with_capacity(sides as usize + 2);
+        let mut indices: Vec<u32> = Vec::with_capacity(sides as usize * 3);
 
+        // Unlike a closed polygon, an arc needs both end points, so there is one more rim vertex than sides.
         vertices.push(Vertex::new(pivot.x, pivot.y, 0., 0., color));
-        for i in 0..sides {
+        for i in 0..=sides {
             vertices.push(Vertex::new(
-                pivot.x + radius * (i as f32 / (sides - 1) as f32 * arc_size + begin_angle).cos(),
-                pivot.y + radius * (i as f32 / (sides - 1) as f32 * arc_size + begin_angle).sin(),
+                pivot.x + radius * (i as f32 / sides as f32 * arc_size + begin_angle).cos(),
+                pivot.y + radius * (i as f32 / sides as f32 * arc_size + begin_angle).sin(),
                 0.,
                 0.,
                 color,
             ));
-
-            indices.extend([0, (i % sides) as u16 + 1, ((i + 1) % sides) as u16 + 1]);
+        }
+        for i in 0..sides as u32 {
+            indices.extend([0, i + 1, i + 2]);
         }
 
-        self.create_draw_call(
-            vertices.into_boxed_slice(),
-            indices.as_slice(),
-            self.default_texture,
-        );
+        // Split like any other mesh when over the per-call limits
+        self.draw_mesh(Mesh { vertices, indices });
     }
 
     pub fn draw_circle(&mut self, pivot: &Point, radius: f32, color: Color) {
@@ -253,8 +736,19 @@ impl DrawContext {
     ) {
         let rotation = (rotation - 90.).to_radians();
 
-        let mut vertices: Vec<Vertex> = Vec::with_capacity((sides + 1) as usize);
-        let mut indices: Vec<u16> = Vec::with_capacity((sides * 3) as usize);
+        if self.anti_aliasing {
+            let outline: Vec<Vec2> = (0..sides)
+                .map(|i| {
+                    let angle = i as f32 / sides as f32 * 2. * PI + rotation;
+                    Vec2::new(pivot.x, pivot.y) + radius * Vec2::new(angle.cos(), angle.sin())
+                })
+                .collect();
+            self.fill_anti_aliased(&outline, color);
+            return;
+        }
+
+        let mut vertices: Vec<Vertex> = Vec::with_capacity(sides as usize + 1);
+        let mut indices: Vec<u32> = Vec::with_capacity(sides as usize * 3);
 
         vertices.push(Vertex::new(pivot.x, pivot.y, 0., 0., color));
         for i in 0..sides {
@@ -266,14 +760,11 @@ impl DrawContext {
                 color,
             ));
 
-            indices.extend([0, (i % sides) as u16 + 1, ((i + 1) % sides) as u16 + 1]);
+            indices.extend([0, (i % sides) as u32 + 1, ((i + 1) % sides) as u32 + 1]);
         }
 
-        self.create_draw_call(
-            vertices.into_boxed_slice(),
-            indices.as_slice(),
-            self.default_texture,
-        );
+        // Split like any other mesh when over the per-call limits
+        self.draw_mesh(Mesh { vertices, indices });
     }
 
     pub fn draw_line(
@@ -305,11 +796,238 @@ impl DrawContext {
             Vertex::new(  end_point.x + mx,   end_point.y + my, 0., 0., color),
             Vertex::new(  end_point.x - mx,   end_point.y - my, 0., 0., color),
         ]);
-        let indices: [u16; 6] = [0, 1, 3, 0, 3, 2];
+        let indices: [u32; 6] = [0, 1, 3, 0, 3, 2];
+
+        if self.anti_aliasing {
+            let outline = [0, 2, 3, 1].map(|i| Vec2::from(vertices[i].pos));
+            self.fill_anti_aliased(&outline, color);
+            return;
+        }
 
         self.create_draw_call(vertices, indices.as_slice(), self.default_texture);
     }
 
+    /// Stroke the line through `points`, also joining the last point back to the first when `closed`.
+    /// The stroke is centered on the line.
+    pub fn draw_polyline(
+        &mut self,
+        points: &[Point],
+        closed: bool,
+        style: StrokeStyle,
+        color: Color,
+    ) {
+        let points: Vec<Vec2> = points
+            .iter()
+            .map(|point| Vec2::new(point.x, point.y))
+            .collect();
+        let mut mesh = Mesh::default();
+        mesh.stroke_polyline(&points, closed, style, color);
+
+        self.draw_mesh(mesh);
+    }
+
+    /// Outline of the polygon `draw_poly_ext` fills, centered on its edges.
+    pub fn draw_poly_lines(
+        &mut self,
+        pivot: &Point,
+        radius: f32,
+        sides: u8,
+        rotation: f32,
+        thickness: f32,
+        color: Color,
+    ) {
+        let rotation = (rotation - 90.).to_radians();
+        let outline: Vec<Vec2> = (0..sides)
+            .map(|i| {
+                let angle = i as f32 / sides as f32 * 2. * PI + rotation;
+                Vec2::new(pivot.x, pivot.y) + radius * Vec2::new(angle.cos(), angle.sin())
+            })
+            .collect();
+
+        let mut mesh = Mesh::default();
+        let style = StrokeStyle {
+            thickness,
+            ..Default::default()
+        };
+        mesh.stroke_polyline(&outline, true, style, color);
+
+        self.draw_mesh(mesh);
+    }
+
+    pub fn draw_circle_lines(&mut self, pivot: &Point, radius: f32, thickness: f32, color: Color) {
+        self.draw_poly_lines(pivot, radius, 40, 0., thickness, color);
+    }
+
+    /// Fill the inside of `path`, every subpath being closed implicitly.
+    pub fn draw_path(&mut self, path: &Path, fill_rule: FillRule, color: Color) {
+        let mut mesh = Mesh::default();
+        self.fill_path_mesh(&mut mesh, path, fill_rule, color);
+
+        self.draw_mesh(mesh);
+    }
+
+    fn fill_path_mesh(&self, mesh: &mut Mesh, path: &Path, fill_rule: FillRule, color: Color) {
+        let polylines = path.flatten(self.flattening_tolerance());
+        for [top_left, top_right, bottom_left, bottom_right] in
+            fill_trapezoids(&polylines, fill_rule)
+        {
+            let first = mesh.push_vertex(top_left, color);
+            mesh.push_vertex(bottom_left, color);
+            mesh.push_vertex(top_right, color);
+            mesh.push_vertex(bottom_right, color);
+            mesh.indices
+                .extend([first, first + 1, first + 3, first, first + 3, first + 2]);
+        }
+    }
+
+    pub fn draw_path_stroke(&mut self, path: &Path, style: StrokeStyle, color: Color) {
+        let mut mesh = Mesh::default();
+        for polyline in path.flatten(self.flattening_tolerance()) {
+            mesh.stroke_polyline(&polyline.points, polyline.closed, style, color);
+        }
+
+        self.draw_mesh(mesh);
+    }
+
+    /// Largest distance between a curve and its flattened segments, a quarter of a pixel.
+    fn flattening_tolerance(&self) -> f32 {
+        self.pixel_size() / 4.
+    }
+
+    /// Size of a physical pixel in the coordinates of the current transform.
+    fn pixel_size(&self) -> f32 {
+        let scale = self.transform_stack.last().map_or(1., |transform| {
+            transform
+                .matrix2
+                .x_axis
+                .length()
+                .max(transform.matrix2.y_axis.length())
+        });
+
+        1. / (scale * self.target_dpi_scale()).max(f32::EPSILON)
+    }
+
+    /// Physical pixels per unit of whatever is being drawn into, render targets using pixels as units.
+    fn target_dpi_scale(&self) -> f32 {
+        if self.target_stack.is_empty() {
+            self.dpi_scale
+        } else {
+            1.
+        }
+    }
+
+    /// Submit the triangles of `mesh` colored by `gradient` instead of their vertex colors.
+    fn try_draw_mesh_gradient(&mut self, mesh: Mesh, gradient: &Gradient) -> Result<(), Error> {
+        let mut shader = GradientShader::try_new(gradient, self.pixel_size())?;
+        let mut triangles = Vec::new();
+        for triangle in mesh.indices.chunks_exact(3) {
+            let triangle = [0, 1, 2].map(|i| Vec2::from(mesh.vertices[triangle[i] as usize].pos));
+            shader.shade(triangle, &mut |shaded| triangles.push(shaded));
+        }
+
+        let mut shaded = Mesh::default();
+        for triangle in triangles {
+            let first = shaded.vertices.len() as u32;
+            for (point, color) in triangle {
+                shaded.push_vertex(point, color);
+            }
+            shaded.indices.extend([first, first + 1, first + 2]);
+        }
+
+        self.draw_mesh(shaded);
+        Ok(())
+    }
+
+    pub fn draw_rect_gradient(&mut self, x: f32, y: f32, w: f32, h: f32, gradient: &Gradient) {
+        self.try_draw_rect_gradient(x, y, w, h, gradient)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    /// Like `draw_rect_gradient`, returning an error for a gradient without color stops.
+    pub fn try_draw_rect_gradient(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        gradient: &Gradient,
+    ) -> Result<(), Error> {
+        let mut mesh = Mesh::default();
+        mesh.fill_convex(
+            &[
+                Vec2::new(x, y),
+                Vec2::new(x + w, y),
+                Vec2::new(x + w, y + h),
+                Vec2::new(x, y + h),
+            ],
+            Color::from_rgba8(0, 0, 0, 0),
+        );
+
+        self.try_draw_mesh_gradient(mesh, gradient)
+    }
+
+    pub fn draw_rounded_rect_gradient(
+        &mut self,
+        rect: Rect,
+        radii: CornerRadii,
+        gradient: &Gradient,
+    ) {
+        self.try_draw_rounded_rect_gradient(rect, radii, gradient)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    pub fn try_draw_rounded_rect_gradient(
+        &mut self,
+        rect: Rect,
+        radii: CornerRadii,
+        gradient: &Gradient,
+    ) -> Result<(), Error> {
+        let mut mesh = Mesh::default();
+        let outline = rounded_rect_outline(rect, radii, BorderWidths::default());
+        mesh.fill_convex(&outline.inner, Color::from_rgba8(0, 0, 0, 0));
+
+        self.try_draw_mesh_gradient(mesh, gradient)
+    }
+
+    pub fn draw_circle_gradient(&mut self, pivot: &Point, radius: f32, gradient: &Gradient) {
+        self.try_draw_circle_gradient(pivot, radius, gradient)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    pub fn try_draw_circle_gradient(
+        &mut self,
+        pivot: &Point,
+        radius: f32,
+        gradient: &Gradient,
+    ) -> Result<(), Error> {
+        let outline: Vec<Vec2> = (0..40)
+            .map(|i| {
+                Vec2::new(pivot.x, pivot.y) + radius * Vec2::from_angle(i as f32 / 40. * 2. * PI)
+            })
+            .collect();
+        let mut mesh = Mesh::default();
+        mesh.fill_convex(&outline, Color::from_rgba8(0, 0, 0, 0));
+
+        self.try_draw_mesh_gradient(mesh, gradient)
+    }
+
+    pub fn draw_path_gradient(&mut self, path: &Path, fill_rule: FillRule, gradient: &Gradient) {
+        self.try_draw_path_gradient(path, fill_rule, gradient)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    pub fn try_draw_path_gradient(
+        &mut self,
+        path: &Path,
+        fill_rule: FillRule,
+        gradient: &Gradient,
+    ) -> Result<(), Error> {
+        let mut mesh = Mesh::default();
+        self.fill_path_mesh(&mut mesh, path, fill_rule, Color::from_rgba8(0, 0, 0, 0));
+
+        self.try_draw_mesh_gradient(mesh, gradient)
+    }
+
     pub fn draw_texture(
         &mut self,
         x: f32,
@@ -319,153 +1037,992 @@ impl DrawContext {
         texture: TextureArea,
         tint: Color,
     ) {
+        self.try_draw_texture(x, y, w, h, texture, tint)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    /// Like `draw_texture`, returning an error when drawing a render target into itself.
+    pub fn try_draw_texture(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        texture: TextureArea,
+        tint: Color,
+    ) -> Result<(), Error> {
+        let quad = TexturedQuad {
+            rect: Rect { x, y, w, h },
+            uv_location: texture.location,
+            uv_size: texture.size,
+        };
+        let indices: [u32; 6] = [0, 1, 3, 0, 3, 2];
+
+        self.try_create_draw_call(Box::new(quad.vertices(tint)), &indices, texture.texture)
+    }
+
+    /// Draw `texture` over `rect` with its corners kept at their size, see `NinePatch`.
+    /// Borders are shrunk when `rect` is too small to fit them.
+    pub fn draw_nine_patch(
+        &mut self,
+        rect: Rect,
+        texture: TextureArea,
+        patch: NinePatch,
+        tint: Color,
+    ) {
+        self.try_draw_nine_patch(rect, texture, patch, tint)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    pub fn try_draw_nine_patch(
+        &mut self,
+        rect: Rect,
+        texture: TextureArea,
+        patch: NinePatch,
+        tint: Color,
+    ) -> Result<(), Error> {
+        let insets = patch.insets;
+        let tile = patch.mode == NinePatchMode::Tile;
+        let columns = nine_patch_spans(
+            rect.x,
+            rect.w,
+            (insets.left, insets.right),
+            patch.image_size.x,
+            patch.scale,
+            tile,
+        );
+        let rows = nine_patch_spans(
+            rect.y,
+            rect.h,
+            (insets.top, insets.bottom),
+            patch.image_size.y,
+            patch.scale,
+            tile,
+        );
+
+        // Texture coordinates per image pixel
+        let texel = texture.size / patch.image_size;
+        let mut quads = Vec::new();
+        for row in &rows {
+            for column in &columns {
+                quads.push(TexturedQuad {
+                    rect: Rect {
+                        x: column.start,
+                        y: row.start,
+                        w: column.size,
+                        h: row.size,
+                    },
+                    uv_location: texture.location
+                        + Vec2::new(column.source_start, row.source_start) * texel,
+                    uv_size: Vec2::new(column.source_size, row.source_size) * texel,
+                });
+            }
+        }
+
+        self.try_draw_textured_quads(texture.texture, &quads, tint)
+    }
+
+    pub fn draw_rounded_rect(&mut self, rect: Rect, radii: CornerRadii, color: Color) {
+        let mut mesh = Mesh::default();
+        let outline = rounded_rect_outline(rect, radii, BorderWidths::default());
+        mesh.fill_convex(&outline.inner, color);
+
+        self.draw_mesh(mesh);
+    }
+
+    /// Only the border of a rounded rectangle, `widths` growing inwards from its edges.
+    pub fn draw_rounded_rect_border(
+        &mut self,
+        rect: Rect,
+        radii: CornerRadii,
+        widths: BorderWidths,
+        color: Color,
+    ) {
+        let mut mesh = Mesh::default();
+        let outline = rounded_rect_outline(rect, radii, widths);
+        mesh.stroke_between(&outline.outer, &outline.inner, color);
+
+        self.draw_mesh(mesh);
+    }
+
+    /// A filled rounded rectangle with a border, batched as a single mesh.
+    pub fn draw_rounded_rect_bordered(
+        &mut self,
+        rect: Rect,
+        radii: CornerRadii,
+        widths: BorderWidths,
+        fill: Color,
+        border: Color,
+    ) {
+        let mut mesh = Mesh::default();
+        let outline = rounded_rect_outline(rect, radii, widths);
+        mesh.fill_convex(&outline.inner, fill);
+        mesh.stroke_between(&outline.outer, &outline.inner, border);
+
+        self.draw_mesh(mesh);
+    }
+
+    /// Soft shadow of a rounded rectangle, like a CSS `box-shadow`: the box is grown by `spread`,
+    /// moved by `offset` and blurred over `blur` units on each side of its edges.
+    pub fn draw_box_shadow(
+        &mut self,
+        rect: Rect,
+        corner_radius: f32,
+        blur: f32,
+        spread: f32,
+        offset: Vec2,
+        color: Color,
+    ) {
+        let half_size = (Vec2::new(rect.w, rect.h) / 2. + spread).max(Vec2::ZERO);
+        let center = Vec2::new(rect.x + rect.w / 2., rect.y + rect.h / 2.) + offset;
+        let corner_radius = (corner_radius + spread).clamp(0., half_size.min_element());
+        // The shader divides by sigma, and a tiny one still gives a sharp edge
+        let sigma = (blur / 2.).max(1e-3);
+
+        // Far enough for the blur to have faded out completely
+        let extent = half_size + 3. * sigma;
         #[rustfmt::skip]
         let vertices = Box::new([
-            Vertex::new(    x,     y,                  texture.location.x,                  texture.location.y, tint),
-            Vertex::new(    x, y + h,                  texture.location.x, texture.location.y + texture.size.y, tint),
-            Vertex::new(x + w,     y, texture.location.x + texture.size.x,                  texture.location.y, tint),
-            Vertex::new(x + w, y + h, texture.location.x + texture.size.x, texture.location.y + texture.size.y, tint)
-        ]);
-        let indices: [u16; 6] = [0, 1, 3, 0, 3, 2];
+            Vec2::new(-extent.x, -extent.y),
+            Vec2::new(-extent.x,  extent.y),
+            Vec2::new( extent.x, -extent.y),
+            Vec2::new( extent.x,  extent.y),
+        ]
+        .map(|local| {
+            let position = center + local;
+            Vertex::new(position.x, position.y, local.x, local.y, color)
+        }));
+        let indices: [u32; 6] = [0, 1, 3, 0, 3, 2];
+
+        self.try_create_shaded_draw_call(
+            vertices,
+            &indices,
+            self.default_texture,
+            Shading::BoxShadow {
+                half_size,
+                corner_radius,
+                sigma,
+            },
+        )
+        .unwrap_or_else(|error| panic!("{}", error));
+    }
 
-        self.create_draw_call(vertices, &indices, texture.texture);
+    /// Size of `text` as `draw_text` would lay it out.
+    #[cfg(feature = "text")]
+    pub fn measure_text(&self, text: &str, font: FontId, size: f32) -> TextDimensions {
+        self.glyph_cache.borrow().measure(text, font, size)
     }
-}
 
-impl<T: EventListener> RendererContext<T> {
-    pub fn new(config: WindowConfig, app_listener: T) -> RendererContext<T> {
-        let backend = Rc::new(RefCell::new(window::new_rendering_backend()));
+    /// Draw `text` with the top-left of its first line at (`x`, `y`), `size` being the line height in units.
+    /// Glyphs are rasterized at the physical pixel size, so they stay sharp on high-DPI screens.
+    #[cfg(feature = "text")]
+    pub fn draw_text(&mut self, text: &str, x: f32, y: f32, font: FontId, size: f32, color: Color) {
+        let mut quads = Vec::new();
+        self.glyph_cache.borrow_mut().layout(
+            text,
+            font,
+            size,
+            self.target_dpi_scale(),
+            Vec2::new(x, y),
+            &mut quads,
+        );
 
-        let backend_info = backend.borrow().info().backend;
-        let mut backend_mut = backend.borrow_mut();
+        for texture_run in quads.chunk_by(|a, b| a.texture == b.texture) {
+            let textured: Vec<TexturedQuad> = texture_run
+                .iter()
+                .map(|quad| TexturedQuad {
+                    rect: Rect {
+                        x: quad.position.x,
+                        y: quad.position.y,
+                        w: quad.size.x,
+                        h: quad.size.y,
+                    },
+                    uv_location: quad.uv_location,
+                    uv_size: quad.uv_size,
+                })
+                .collect();
+
+            // Glyph atlas pages are never render targets
+            self.try_draw_textured_quads(texture_run[0].texture, &textured, color)
+                .unwrap_or_else(|error| panic!("{}", error));
+        }
+    }
 
-        let white_texture = backend_mut.new_texture_from_rgba8(1, 1, &[255, 255, 255, 255]);
+    /// Submit quads sharing `texture`, split into chunks under the per-call limits.
+    fn try_draw_textured_quads(
+        &mut self,
+        texture: TextureId,
+        quads: &[TexturedQuad],
+        tint: Color,
+    ) -> Result<(), Error> {
+        // create_draw_call only merges each chunk into the batch, so the chunks still end up together
+        let max_quads = ((self.max_vertex_per_call - 1) / 4).min((self.max_index_per_call - 1) / 6);
+
+        for chunk in quads.chunks(max_quads) {
+            let mut vertices = Vec::with_capacity(chunk.len() * 4);
+            let mut indices = Vec::with_capacity(chunk.len() * 6);
+
+            for quad in chunk {
+                let base = vertices.len() as u32;
+                vertices.extend(quad.vertices(tint));
+                indices.extend([0, 1, 3, 0, 3, 2].map(|index| base + index));
+            }
 
-        let vertex_buffer = backend_mut.new_buffer(
-            miniquad::BufferType::VertexBuffer,
-            miniquad::BufferUsage::Stream,
-            miniquad::BufferSource::empty::<Vertex>(config.max_vertices_per_draw),
-        );
+            self.try_create_draw_call(vertices.into_boxed_slice(), &indices, texture)?;
+        }
+        Ok(())
+    }
+}
 
-        let index_buffer = backend_mut.new_buffer(
-            miniquad::BufferType::IndexBuffer,
-            miniquad::BufferUsage::Stream,
-            miniquad::BufferSource::empty::<u16>(config.max_indices_per_draw),
-        );
+impl<T: EventListener> RendererContext<T> {
+    pub fn new(config: WindowConfig, app_listener: T) -> RendererContext<T> {
+        Self::try_new(config, app_listener).unwrap_or_else(|error| panic!("{}", error))
+    }
 
-        let bindings = Bindings {
-            vertex_buffers: vec![vertex_buffer],
-            index_buffer,
-            images: vec![white_texture],
-        };
+    /// Like `new`, returning an error for an invalid config or shaders that do not build.
+    pub fn try_new(config: WindowConfig, app_listener: T) -> Result<RendererContext<T>, Error> {
+        config.validate()?;
+        let backend = GpuBackend::try_new(&config)?;
 
-        let shader = backend_mut
-            .new_shader(
-                match backend_info {
-                    miniquad::Backend::OpenGl => miniquad::ShaderSource::Glsl {
-                        vertex: shader::VERTEX,
-                        fragment: shader::FRAGMENT,
-                    },
-                    miniquad::Backend::Metal => miniquad::ShaderSource::Msl {
-                        program: shader::METAL,
-                    },
-                },
-                shader::meta(),
-            )
-            .unwrap();
+        RendererContext::try_with_backend(config, app_listener, backend)
+    }
+}
 
-        let pipeline = backend_mut.new_pipeline(
-            &[BufferLayout::default()],
-            &[
-                VertexAttribute::new("in_pos", miniquad::VertexFormat::Float2),
-                VertexAttribute::new("in_color", miniquad::VertexFormat::Float4),
-                VertexAttribute::new("in_texcoord", miniquad::VertexFormat::Float2),
-            ],
-            shader,
-            PipelineParams::default(),
-        );
+impl<T: EventListener, B: Backend + 'static> RendererContext<T, B> {
+    /// Drive `app_listener` with any backend, e.g. a `SoftwareBackend` for headless runs.
+    pub fn with_backend(config: WindowConfig, app_listener: T, backend: B) -> Self {
+        Self::try_with_backend(config, app_listener, backend)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
 
-        // Drop context so we can use the immutable ref
-        drop(backend_mut);
+    /// Like `with_backend`, returning an error for an invalid config.
+    pub fn try_with_backend(
+        config: WindowConfig,
+        app_listener: T,
+        mut backend: B,
+    ) -> Result<Self, Error> {
+        config.validate()?;
+        // Past the reach of 16-bit indices, draw calls are split rather than wrapping around
+        let index_format = config
+            .index_format
+            .resolve(backend.supports_u32_indices())?;
+        let max_vertices_per_draw = config
+            .max_vertices_per_draw
+            .min(index_format.max_vertices());
+
+        let white_texture = backend.new_texture(
+            1,
+            1,
+            TextureFormat::RGBA8,
+            &[255, 255, 255, 255],
+            TextureOptions::default(),
+        );
 
-        let (width, height) = miniquad::window::screen_size();
-        let dpi = miniquad::window::dpi_scale();
+        let backend = Rc::new(RefCell::new(backend));
+        #[cfg(feature = "text")]
+        let glyph_cache = Rc::new(RefCell::new(GlyphCache::new(backend.clone())));
 
-        RendererContext {
+        let mut renderer = RendererContext {
             draw_context: DrawContext::new(
                 white_texture,
-                config.max_vertices_per_draw,
+                max_vertices_per_draw,
                 config.max_indices_per_draw,
+                config.anti_aliasing,
+                #[cfg(feature = "text")]
+                glyph_cache.clone(),
+            ),
+            texture_context: TextureContext::new(
+                backend.clone(),
+                #[cfg(feature = "text")]
+                glyph_cache,
             ),
-            texture_context: TextureContext::new(backend.clone()),
             app_listener,
-            uniform: Uniforms {
-                model: Mat4::IDENTITY,
-                projection: Mat4::orthographic_rh_gl(0., width / dpi, height / dpi, 0., -1., 1.),
-            },
-            pipeline,
-            bindings,
             backend,
-            last_update_time: miniquad::date::now(),
+            frame_timer: FrameTimer::new(&config, miniquad::date::now()),
+            focused: true,
+        };
+        renderer.app_listener.init(&renderer.texture_context);
+
+        Ok(renderer)
+    }
+
+    pub fn backend(&self) -> Ref<'_, B> {
+        self.backend.borrow()
+    }
+}
+
+impl<T: EventListener, B: Backend> RendererContext<T, B> {
+    /// Convert a window position in physical pixels to the logical units used for drawing.
+    fn to_logical(&self, x: f32, y: f32) -> (f32, f32) {
+        let dpi = self.backend.borrow().dpi_scale();
+
+        (x / dpi, y / dpi)
+    }
+
+    /// Sleep until the frame is due under `WindowConfig::target_fps`, returning the time after.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn wait_for_frame(&self, current_time: f64) -> f64 {
+        let wait_time = self.frame_timer.wait_time(current_time);
+        if wait_time <= 0. {
+            return current_time;
         }
+
+        std::thread::sleep(std::time::Duration::from_secs_f64(wait_time));
+        miniquad::date::now()
     }
 }
 
-impl<T: EventListener> EventHandler for RendererContext<T> {
+impl<T: EventListener, B: Backend> EventHandler for RendererContext<T, B> {
     fn resize_event(&mut self, width: f32, height: f32) {
-        let dpi = miniquad::window::dpi_scale();
+        let mut backend = self.backend.borrow_mut();
+        backend.resize(width, height);
+        let (width, height) = backend.screen_size();
+        drop(backend);
+
+        self.app_listener.resize(width, height);
+    }
+
+    fn window_minimized_event(&mut self) {
+        self.app_listener.minimized();
+        if self.focused {
+            self.focused = false;
+            self.app_listener.focus_lost();
+        }
+    }
+
+    fn window_restored_event(&mut self) {
+        self.app_listener.restored();
+        if !self.focused {
+            self.focused = true;
+            self.app_listener.focus_gained();
+        }
+    }
+
+    fn quit_requested_event(&mut self) {
+        if !self.app_listener.quit_requested() {
+            self.backend.borrow_mut().cancel_quit();
+        }
+    }
+
+    fn files_dropped_event(&mut self) {
+        let files = self.backend.borrow().dropped_files();
+        self.app_listener.files_dropped(&files);
+    }
+
+    fn mouse_motion_event(&mut self, x: f32, y: f32) {
+        let (x, y) = self.to_logical(x, y);
+        self.app_listener.input(&InputEvent::MouseMotion { x, y });
+    }
 
-        self.uniform.projection =
-            Mat4::orthographic_rh_gl(0., width / dpi, height / dpi, 0., -1., 1.);
+    fn mouse_wheel_event(&mut self, dx: f32, dy: f32) {
+        self.app_listener.input(&InputEvent::MouseWheel { dx, dy });
+    }
+
+    fn mouse_button_down_event(&mut self, button: MouseButton, x: f32, y: f32) {
+        let (x, y) = self.to_logical(x, y);
+        self.app_listener
+            .input(&InputEvent::MouseButtonDown { button, x, y });
+    }
+
+    fn mouse_button_up_event(&mut self, button: MouseButton, x: f32, y: f32) {
+        let (x, y) = self.to_logical(x, y);
+        self.app_listener
+            .input(&InputEvent::MouseButtonUp { button, x, y });
+    }
+
+    fn char_event(&mut self, character: char, modifiers: KeyMods, repeat: bool) {
+        self.app_listener.input(&InputEvent::Char {
+            character,
+            modifiers,
+            repeat,
+        });
+    }
+
+    fn key_down_event(&mut self, keycode: KeyCode, modifiers: KeyMods, repeat: bool) {
+        self.app_listener.input(&InputEvent::KeyDown {
+            keycode,
+            modifiers,
+            repeat,
+        });
+    }
+
+    fn key_up_event(&mut self, keycode: KeyCode, modifiers: KeyMods) {
+        self.app_listener
+            .input(&InputEvent::KeyUp { keycode, modifiers });
+    }
+
+    fn touch_event(&mut self, phase: TouchPhase, id: u64, x: f32, y: f32) {
+        let (logical_x, logical_y) = self.to_logical(x, y);
+        self.app_listener.input(&InputEvent::Touch {
+            phase,
+            id,
+            x: logical_x,
+            y: logical_y,
+        });
+
+        // Keep miniquad's mouse emulation so mouse-only apps still work on touch screens
+        match phase {
+            TouchPhase::Started => self.mouse_button_down_event(MouseButton::Left, x, y),
+            TouchPhase::Ended => self.mouse_button_up_event(MouseButton::Left, x, y),
+            TouchPhase::Moved => self.mouse_motion_event(x, y),
+            TouchPhase::Cancelled => {}
+        }
     }
 
     fn update(&mut self) {
         let current_time = miniquad::date::now();
-        self.app_listener
-            .update(&self.texture_context, current_time - self.last_update_time);
-        self.last_update_time = current_time;
+        // Blocking the browser's event loop would only delay the frame it already scheduled
+        #[cfg(not(target_arch = "wasm32"))]
+        let current_time = self.wait_for_frame(current_time);
+
+        let steps = self.frame_timer.tick(current_time);
+        for _ in 0..steps.updates {
+            self.app_listener.update(&self.texture_context, steps.dt);
+        }
     }
 
     fn draw(&mut self) {
+        let backend = self.backend.borrow();
+        self.draw_context.screen_size = backend.screen_size();
+        self.draw_context.dpi_scale = backend.dpi_scale();
+        drop(backend);
+        self.draw_context.interpolation_alpha = self.frame_timer.alpha();
+
         self.app_listener.draw(&mut self.draw_context);
 
-        let mut context = self.backend.borrow_mut();
+        self.backend.borrow_mut().render(&self.draw_context);
+
+        self.draw_context.clear();
+    }
+}
 
-        // [TODO] Expose the clear color to the user
-        // Technically can be exposed via drawing a rect but
-        context.clear(Some((0., 0., 0., 255.)), None, None);
+// A good-enough circle approx is a 36-sided regular polygon
+// So we can scale down the amount of sides needed based on the arc_size to save vertices.
+fn arc_sides(arc_size: f32) -> u8 {
+    (36. * (arc_size / (2. * PI))).ceil() as u8
+}
 
-        for draw_call in &self.draw_context.draw_call_vec {
-            context.begin_default_pass(miniquad::PassAction::Nothing);
+/// Screen rectangle and texture coordinates of one textured quad.
+struct TexturedQuad {
+    rect: Rect,
+    uv_location: Vec2,
+    uv_size: Vec2,
+}
 
-            context.buffer_update(
-                self.bindings.vertex_buffers[0],
-                miniquad::BufferSource::slice(
-                    &self.draw_context.vertex_buffer[draw_call.vertex_indices_slice.offset
-                        ..(draw_call.vertex_indices_slice.offset
-                            + draw_call.vertex_indices_slice.length)],
-                ),
-            );
-            context.buffer_update(
-                self.bindings.index_buffer,
-                miniquad::BufferSource::slice(
-                    &self.draw_context.index_buffer[draw_call.index_indices_slice.offset
-                        ..(draw_call.index_indices_slice.offset
-                            + draw_call.index_indices_slice.length)],
-                ),
-            );
-            self.bindings.images[0] = draw_call.texture;
+impl TexturedQuad {
+    /// Corners in the order of the `[0, 1, 3, 0, 3, 2]` quad indices.
+    fn vertices(&self, color: Color) -> [Vertex; 4] {
+        let Rect { x, y, w, h } = self.rect;
+        let uv = self.uv_location;
+        let uv_size = self.uv_size;
 
-            context.apply_pipeline(&self.pipeline);
-            context.apply_bindings(&self.bindings);
-            context.apply_uniforms(UniformsSource::table(&self.uniform));
+        #[rustfmt::skip]
+        let vertices = [
+            Vertex::new(    x,     y,             uv.x,             uv.y, color),
+            Vertex::new(    x, y + h,             uv.x, uv.y + uv_size.y, color),
+            Vertex::new(x + w,     y, uv.x + uv_size.x,             uv.y, color),
+            Vertex::new(x + w, y + h, uv.x + uv_size.x, uv.y + uv_size.y, color),
+        ];
+        vertices
+    }
+}
 
-            context.draw(0, draw_call.index_indices_slice.length as i32, 1);
+/// A column or row of a nine-patch: where it is drawn and which image pixels it shows.
+struct NinePatchSpan {
+    start: f32,
+    size: f32,
+    source_start: f32,
+    source_size: f32,
+}
 
-            context.end_render_pass();
+/// The spans covering `size` units from `start`, for an image `image_size` pixels long with
+/// `borders` pixels at each end. The middle is split into tiles when `tile` is set.
+fn nine_patch_spans(
+    start: f32,
+    size: f32,
+    borders: (f32, f32),
+    image_size: f32,
+    scale: f32,
+    tile: bool,
+) -> Vec<NinePatchSpan> {
+    let size = size.max(0.);
+    let (border_start, border_end) = borders;
+    let drawn_borders = (border_start + border_end) * scale;
+    let shrink = if drawn_borders > size {
+        size / drawn_borders
+    } else {
+        1.
+    };
+    let drawn_start = border_start * scale * shrink;
+    let drawn_end = border_end * scale * shrink;
+    let middle_size = size - drawn_start - drawn_end;
+    let source_middle = image_size - border_start - border_end;
+
+    let mut spans = vec![NinePatchSpan {
+        start,
+        size: drawn_start,
+        source_start: 0.,
+        source_size: border_start,
+    }];
+
+    let tile_size = source_middle * scale;
+    if tile && tile_size > 0. {
+        let mut offset = 0.;
+        while offset < middle_size {
+            let drawn = tile_size.min(middle_size - offset);
+            spans.push(NinePatchSpan {
+                start: start + drawn_start + offset,
+                size: drawn,
+                source_start: border_start,
+                source_size: source_middle * drawn / tile_size,
+            });
+            offset += tile_size;
         }
+    } else {
+        spans.push(NinePatchSpan {
+            start: start + drawn_start,
+            size: middle_size,
+            source_start: border_start,
+            source_size: source_middle,
+        });
+    }
 
-        context.commit_frame();
-        self.draw_context.clear();
+    spans.push(NinePatchSpan {
+        start: start + size - drawn_end,
+        size: drawn_end,
+        source_start: image_size - border_end,
+        source_size: border_end,
+    });
+    // Empty borders or middles would only add degenerate quads
+    spans.retain(|span| span.size > 0.);
+    spans
+}
+
+/// Untextured triangles accumulated before being handed to `create_draw_call`.
+#[derive(Default)]
+struct Mesh {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+
+impl Mesh {
+    fn push_vertex(&mut self, point: Vec2, color: Color) -> u32 {
+        self.vertices
+            .push(Vertex::new(point.x, point.y, 0., 0., color));
+        (self.vertices.len() - 1) as u32
+    }
+
+    /// Triangle fan around the centroid of a convex, closed outline.
+    fn fill_convex(&mut self, outline: &[Vec2], color: Color) {
+        let center = outline.iter().copied().sum::<Vec2>() / outline.len() as f32;
+        let center = self.push_vertex(center, color);
+        let first = self.vertices.len() as u32;
+        for point in outline {
+            self.push_vertex(*point, color);
+        }
+
+        let count = outline.len() as u32;
+        for i in 0..count {
+            self.indices
+                .extend([center, first + i, first + (i + 1) % count]);
+        }
+    }
+
+    /// Fill `outline` shrunk by half the `fringe`, surrounded by a band fading out to
+    /// half the `fringe` outside of it. Edges on pixel boundaries stay crisp that way.
+    fn fill_anti_aliased(&mut self, outline: &[Vec2], color: Color, fringe: f32) {
+        let count = outline.len();
+        if count < 3 {
+            return;
+        }
+
+        // Shoelace formula, only the sign matters to know which side is outside
+        let area: f32 = (0..count)
+            .map(|i| outline[i].perp_dot(outline[(i + 1) % count]))
+            .sum();
+        let outward = |from: Vec2, to: Vec2| {
+            let normal = (to - from).normalize_or_zero().perp();
+            if area > 0. { -normal } else { normal }
+        };
+
+        let transparent = color.with_alpha(0.);
+        let first = self.vertices.len() as u32;
+        for i in 0..count {
+            let previous = outline[(i + count - 1) % count];
+            let point = outline[i];
+            let next = outline[(i + 1) % count];
+
+            let normal_in = outward(previous, point);
+            let normal_out = outward(point, next);
+            let miter = (normal_in + normal_out).normalize_or(normal_out);
+            // Keep the fringe width constant along both edges, within reason on sharp corners
+            let offset = miter / miter.dot(normal_out).max(0.25) * fringe / 2.;
+
+            self.push_vertex(point - offset, color);
+            self.push_vertex(point + offset, transparent);
+        }
+
+        let count = count as u32;
+        for i in 1..count - 1 {
+            self.indices
+                .extend([first, first + i * 2, first + (i + 1) * 2]);
+        }
+        for i in 0..count {
+            let inner = first + i * 2;
+            let outer = inner + 1;
+            let next_inner = first + (i + 1) % count * 2;
+            let next_outer = next_inner + 1;
+            self.indices
+                .extend([inner, outer, next_outer, inner, next_outer, next_inner]);
+        }
+    }
+
+    /// Triangle fan from `pivot` over an arc around `center`, starting in `direction` and turning by `sweep` radians.
+    fn fan_arc(
+        &mut self,
+        pivot: Vec2,
+        center: Vec2,
+        direction: Vec2,
+        sweep: f32,
+        radius: f32,
+        color: Color,
+    ) {
+        let sides = arc_sides(sweep.abs()).max(1);
+        let pivot = self.push_vertex(pivot, color);
+        for i in 0..=sides {
+            let rotation = Vec2::from_angle(i as f32 / sides as f32 * sweep);
+            self.push_vertex(center + rotation.rotate(direction) * radius, color);
+        }
+        for i in 0..sides as u32 {
+            self.indices.extend([pivot, pivot + i + 1, pivot + i + 2]);
+        }
+    }
+
+    fn stroke_polyline(&mut self, points: &[Vec2], closed: bool, style: StrokeStyle, color: Color) {
+        let half = style.thickness / 2.;
+        let mut points = points.to_vec();
+        points.dedup_by(|a, b| a.distance_squared(*b) < 1e-6);
+        if closed && points.len() > 2 && points[0].distance_squared(points[points.len() - 1]) < 1e-6
+        {
+            points.pop();
+        }
+        if points.len() < 2 || half <= 0. {
+            return;
+        }
+
+        let count = points.len();
+        let segment_count = if closed { count } else { count - 1 };
+        let directions: Vec<Vec2> = (0..segment_count)
+            .map(|i| (points[(i + 1) % count] - points[i]).normalize())
+            .collect();
+        let lengths: Vec<f32> = (0..segment_count)
+            .map(|i| points[(i + 1) % count].distance(points[i]))
+            .collect();
+
+        // (left, right) corners at both ends of each segment, moved by the joins and caps below
+        let mut starts = Vec::with_capacity(segment_count);
+        let mut ends = Vec::with_capacity(segment_count);
+        for i in 0..segment_count {
+            let normal = directions[i].perp() * half;
+            starts.push((points[i] + normal, points[i] - normal));
+            ends.push((
+                points[(i + 1) % count] + normal,
+                points[(i + 1) % count] - normal,
+            ));
+        }
+
+        let joints = if closed { 0..count } else { 1..count - 1 };
+        for i in joints {
+            let incoming = (i + segment_count - 1) % segment_count;
+            let outgoing = i;
+            let point = points[i];
+            let (d0, d1) = (directions[incoming], directions[outgoing]);
+            let cross = d0.perp_dot(d1);
+            if cross.abs() < 1e-6 && d0.dot(d1) > 0. {
+                continue;
+            }
+
+            // Side of the line on the outside of the turn, as a sign of the left normal
+            let side = if cross > 0. { -1. } else { 1. };
+            let outer_in = point + side * d0.perp() * half;
+            let outer_out = point + side * d1.perp() * half;
+            let miter = (d0.perp() + d1.perp()).normalize_or_zero() * side;
+            let cos_half = miter.dot(side * d0.perp());
+
+            // Meet both segments at the intersection of their inner edges when they are long enough,
+            // so translucent strokes do not overlap themselves there
+            let mut pivot = point;
+            if cos_half > 1e-3 {
+                let inset = half * (1. - cos_half * cos_half).sqrt() / cos_half;
+                if inset * 2. <= lengths[incoming].min(lengths[outgoing]) {
+                    pivot = point - miter * half / cos_half;
+                    if side < 0. {
+                        ends[incoming].0 = pivot;
+                        starts[outgoing].0 = pivot;
+                    } else {
+                        ends[incoming].1 = pivot;
+                        starts[outgoing].1 = pivot;
+                    }
+                }
+            }
+
+            match style.join {
+                LineJoin::Miter if cos_half > 1e-3 && 1. / cos_half <= style.miter_limit => {
+                    let tip = point + miter * half / cos_half;
+                    let first = self.push_vertex(pivot, color);
+                    self.push_vertex(outer_in, color);
+                    self.push_vertex(tip, color);
+                    self.push_vertex(outer_out, color);
+                    self.indices
+                        .extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+                }
+                LineJoin::Round => {
+                    let from = side * d0.perp();
+                    // A full reversal is ambiguous, go around the front of the incoming segment
+                    let sweep = if cos_half > 1e-3 {
+                        from.angle_to(side * d1.perp())
+                    } else {
+                        -side * PI
+                    };
+                    self.fan_arc(pivot, point, from, sweep, half, color);
+                }
+                _ => {
+                    let first = self.push_vertex(pivot, color);
+                    self.push_vertex(outer_in, color);
+                    self.push_vertex(outer_out, color);
+                    self.indices.extend([first, first + 1, first + 2]);
+                }
+            }
+        }
+
+        if !closed {
+            let first = directions[0];
+            let last = directions[segment_count - 1];
+            match style.cap {
+                LineCap::Butt => {}
+                LineCap::Square => {
+                    starts[0].0 -= first * half;
+                    starts[0].1 -= first * half;
+                    ends[segment_count - 1].0 += last * half;
+                    ends[segment_count - 1].1 += last * half;
+                }
+                LineCap::Round => {
+                    let start = points[0];
+                    let end = points[count - 1];
+                    self.fan_arc(start, start, first.perp(), PI, half, color);
+                    self.fan_arc(end, end, last.perp(), -PI, half, color);
+                }
+            }
+        }
+
+        for (start, end) in starts.iter().zip(&ends) {
+            let first = self.push_vertex(start.0, color);
+            self.push_vertex(start.1, color);
+            self.push_vertex(end.0, color);
+            self.push_vertex(end.1, color);
+            self.indices
+                .extend([first, first + 1, first + 3, first, first + 3, first + 2]);
+        }
+    }
+
+    /// Closed band between two outlines with matching points.
+    fn stroke_between(&mut self, outer: &[Vec2], inner: &[Vec2], color: Color) {
+        let first = self.vertices.len() as u32;
+        for (outer, inner) in outer.iter().zip(inner) {
+            self.push_vertex(*outer, color);
+            self.push_vertex(*inner, color);
+        }
+
+        let count = outer.len() as u32;
+        for i in 0..count {
+            let outer = first + i * 2;
+            let inner = outer + 1;
+            let next_outer = first + (i + 1) % count * 2;
+            let next_inner = next_outer + 1;
+            self.indices
+                .extend([outer, inner, next_inner, outer, next_inner, next_outer]);
+        }
+    }
+}
+
+struct RoundedOutline {
+    outer: Vec<Vec2>,
+    /// Equal to `outer` when there is no border.
+    inner: Vec<Vec2>,
+}
+
+/// Outer and inner outlines of a bordered rounded rectangle, clockwise from the top-left corner.
+///
+/// Both outlines have the same number of points so they can be stitched together. Radii are
+/// scaled down like CSS does when adjacent corners would overlap, and inner corners become
+/// elliptical when the borders on either side of them differ.
+fn rounded_rect_outline(rect: Rect, radii: CornerRadii, widths: BorderWidths) -> RoundedOutline {
+    let Rect { x, y, w, h } = rect;
+    let mut radii = [
+        radii.top_left,
+        radii.top_right,
+        radii.bottom_right,
+        radii.bottom_left,
+    ]
+    .map(|radius| radius.max(0.));
+    let scale = [
+        w / (radii[0] + radii[1]),
+        h / (radii[1] + radii[2]),
+        w / (radii[2] + radii[3]),
+        h / (radii[3] + radii[0]),
+    ]
+    .into_iter()
+    .fold(1_f32, f32::min);
+    radii = radii.map(|radius| radius * scale);
+
+    // Corner centers, and the border widths on the (horizontal, vertical) sides of each corner
+    let centers = [
+        Vec2::new(x + radii[0], y + radii[0]),
+        Vec2::new(x + w - radii[1], y + radii[1]),
+        Vec2::new(x + w - radii[2], y + h - radii[2]),
+        Vec2::new(x + radii[3], y + h - radii[3]),
+    ];
+    let corner_widths = [
+        (widths.left, widths.top),
+        (widths.right, widths.top),
+        (widths.right, widths.bottom),
+        (widths.left, widths.bottom),
+    ];
+    // Direction from each corner center towards the rectangle corner
+    let corner_signs = [
+        Vec2::new(-1., -1.),
+        Vec2::new(1., -1.),
+        Vec2::new(1., 1.),
+        Vec2::new(-1., 1.),
+    ];
+
+    let mut outer = Vec::new();
+    let mut inner = Vec::new();
+    for corner in 0..4 {
+        let radius = radii[corner];
+        let (width_x, width_y) = corner_widths[corner];
+        let sign = corner_signs[corner];
+        let begin_angle = PI + corner as f32 * PI / 2.;
+        let sides = if radius > 0. { arc_sides(PI / 2.) } else { 0 };
+
+        // Inner corner center, pulled in when the border is wider than the radius
+        let inner_center = centers[corner]
+            - sign * Vec2::new((width_x - radius).max(0.), (width_y - radius).max(0.));
+        let inner_radius = Vec2::new((radius - width_x).max(0.), (radius - width_y).max(0.));
+
+        for i in 0..=sides {
+            let angle = begin_angle + i as f32 / sides.max(1) as f32 * PI / 2.;
+            let direction = Vec2::new(angle.cos(), angle.sin());
+
+            outer.push(centers[corner] + direction * radius);
+            inner.push(inner_center + direction * inner_radius);
+        }
+    }
+
+    // Keep the inner outline inside the rectangle when the borders are wider than it
+    let min = Vec2::new(x + widths.left.min(w), y + widths.top.min(h));
+    let max = Vec2::new(
+        (x + w - widths.right).max(min.x),
+        (y + h - widths.bottom).max(min.y),
+    );
+    for point in &mut inner {
+        *point = point.clamp(min, max);
+    }
+
+    RoundedOutline { outer, inner }
+}
+
+#[cfg(test)]
+mod tests {
+    use miniquad::RawId;
+
+    use super::*;
+    use crate::{
+        backend::SoftwareBackend,
+        material::{MaterialId, UniformValue},
+    };
+
+    fn draw_context() -> DrawContext {
+        #[cfg(feature = "text")]
+        let backend: Rc<RefCell<dyn Backend>> = Rc::new(RefCell::new(SoftwareBackend::new(16, 16)));
+
+        DrawContext::new(
+            TextureId::from_raw_id(RawId::OpenGl(0)),
+            1000,
+            1000,
+            false,
+            #[cfg(feature = "text")]
+            Rc::new(RefCell::new(GlyphCache::new(backend))),
+        )
+    }
+
+    fn material(id: usize, tint: f32) -> Material {
+        Material::new(MaterialId(id), &[("tint", UniformValue::Float(tint))])
+    }
+
+    /// Draw a rectangle with `material`, or the built-in shader for `None`.
+    fn draw_with(draw_context: &mut DrawContext, material: Option<&Material>) {
+        if let Some(material) = material {
+            draw_context.push_material(material);
+        }
+        draw_context.draw_rect(0., 0., 4., 4., Color::from_rgba8(255, 255, 255, 255));
+        if material.is_some() {
+            draw_context.pop_material();
+        }
+    }
+
+    #[test]
+    fn material_switch_starts_a_draw_call() {
+        let mut draw_context = draw_context();
+
+        draw_with(&mut draw_context, None);
+        draw_with(&mut draw_context, Some(&material(0, 1.)));
+        draw_with(&mut draw_context, Some(&material(1, 1.)));
+        draw_with(&mut draw_context, None);
+
+        let materials: Vec<_> = draw_context
+            .draw_call_vec
+            .iter()
+            .map(|draw_call| draw_call.material.as_ref().map(Material::id))
+            .collect();
+        assert_eq!(
+            materials,
+            [None, Some(MaterialId(0)), Some(MaterialId(1)), None]
+        );
+    }
+
+    #[test]
+    fn uniform_change_starts_a_draw_call() {
+        let mut draw_context = draw_context();
+        let mut tinted = material(0, 1.);
+
+        draw_with(&mut draw_context, Some(&tinted));
+        tinted.set_uniform("tint", UniformValue::Float(0.5));
+        draw_with(&mut draw_context, Some(&tinted));
+
+        assert_eq!(draw_context.draw_call_vec.len(), 2);
+        assert_eq!(
+            draw_context.draw_call_vec[1]
+                .material
+                .as_ref()
+                .unwrap()
+                .values,
+            [UniformValue::Float(0.5)]
+        );
+    }
+
+    #[test]
+    fn identical_materials_batch_together() {
+        let mut draw_context = draw_context();
+        let tinted = material(0, 1.);
+
+        draw_with(&mut draw_context, Some(&tinted));
+        draw_with(&mut draw_context, Some(&tinted.clone()));
+        draw_with(&mut draw_context, Some(&material(0, 1.)));
+
+        assert_eq!(draw_context.draw_call_vec.len(), 1);
+        let draw_call = &draw_context.draw_call_vec[0];
+        assert_eq!(draw_call.vertex_indices_slice.length, 12);
+        assert_eq!(draw_call.index_indices_slice.length, 18);
     }
 }
@@ -87,3 +87,176 @@ pub struct Uniforms {
     pub model: glam::Mat4,
     pub projection: glam::Mat4,
 }
+
+// Box shadows use the closed form from Evan Wallace's "Fast Rounded Rectangle Shadows":
+// the Gaussian blur is integrated exactly along x and sampled four times along y.
+// `uv` is the fragment's offset from the center of the box, in logical units.
+
+pub const BOX_SHADOW_VERTEX: &str = r"
+#version 100
+attribute vec2 in_pos;
+attribute vec4 in_color;
+attribute vec2 in_texcoord;
+
+varying mediump vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 model;
+uniform mat4 projection;
+
+void main() {
+    gl_Position = projection * model * vec4(in_pos, 0, 1);
+    uv = in_texcoord;
+    color = in_color;
+}";
+
+pub const BOX_SHADOW_FRAGMENT: &str = r"
+#version 100
+precision mediump float;
+
+varying mediump vec2 uv;
+varying lowp vec4 color;
+
+// (half width, half height, corner radius, sigma)
+uniform vec4 shadow;
+
+float gaussian(float x, float sigma) {
+    return exp(-(x * x) / (2.0 * sigma * sigma)) / (2.50662827 * sigma);
+}
+
+vec2 erf(vec2 x) {
+    vec2 s = sign(x);
+    vec2 a = abs(x);
+    x = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    x *= x;
+    return s - s / (x * x);
+}
+
+float shadow_x(float x, float y, float sigma, float corner, vec2 half_size) {
+    float delta = min(half_size.y - corner - abs(y), 0.0);
+    float curved = half_size.x - corner + sqrt(max(0.0, corner * corner - delta * delta));
+    vec2 integral = 0.5 + 0.5 * erf((x + vec2(-curved, curved)) * (0.70710678 / sigma));
+    return integral.y - integral.x;
+}
+
+void main() {
+    vec2 half_size = shadow.xy;
+    float corner = shadow.z;
+    float sigma = shadow.w;
+
+    float low = uv.y - half_size.y;
+    float high = uv.y + half_size.y;
+    float start = clamp(-3.0 * sigma, low, high);
+    float end = clamp(3.0 * sigma, low, high);
+
+    float step = (end - start) / 4.0;
+    float y = start + step * 0.5;
+    float value = 0.0;
+    for (int i = 0; i < 4; i++) {
+        value += shadow_x(uv.x, uv.y - y, sigma, corner, half_size) * gaussian(y, sigma) * step;
+        y += step;
+    }
+
+    gl_FragColor = vec4(color.rgb, color.a * value);
+}";
+
+pub const BOX_SHADOW_METAL: &str = r"
+#include <metal_stdlib>
+
+using namespace metal;
+
+struct Uniforms
+{
+    float4x4 model;
+    float4x4 projection;
+    float4 shadow;
+};
+
+struct Vertex
+{
+    float2 in_pos   [[attribute(0)]];
+    float4 in_color [[attribute(1)]];
+    float2 in_texcoord [[attribute(2)]];
+};
+
+struct RasterizerData
+{
+    float4 position [[position]];
+    float4 color [[user(locn0)]];
+    float2 uv [[user(locn1)]];
+};
+
+vertex RasterizerData vertexShader(Vertex v [[stage_in]], constant Uniforms& uniforms [[buffer(0)]])
+{
+    RasterizerData out;
+
+    out.position = uniforms.projection * uniforms.model * float4(v.in_pos, 0, 1);
+    out.color = v.in_color;
+    out.uv = v.in_texcoord;
+
+    return out;
+}
+
+float gaussian(float x, float sigma)
+{
+    return exp(-(x * x) / (2.0 * sigma * sigma)) / (2.50662827 * sigma);
+}
+
+float2 erf(float2 x)
+{
+    float2 s = sign(x);
+    float2 a = abs(x);
+    x = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    x *= x;
+    return s - s / (x * x);
+}
+
+float shadow_x(float x, float y, float sigma, float corner, float2 half_size)
+{
+    float delta = min(half_size.y - corner - abs(y), 0.0);
+    float curved = half_size.x - corner + sqrt(max(0.0, corner * corner - delta * delta));
+    float2 integral = 0.5 + 0.5 * erf((x + float2(-curved, curved)) * (0.70710678 / sigma));
+    return integral.y - integral.x;
+}
+
+fragment float4 fragmentShader(RasterizerData in [[stage_in]], constant Uniforms& uniforms [[buffer(0)]])
+{
+    float2 half_size = uniforms.shadow.xy;
+    float corner = uniforms.shadow.z;
+    float sigma = uniforms.shadow.w;
+
+    float low = in.uv.y - half_size.y;
+    float high = in.uv.y + half_size.y;
+    float start = clamp(-3.0 * sigma, low, high);
+    float end = clamp(3.0 * sigma, low, high);
+
+    float step = (end - start) / 4.0;
+    float y = start + step * 0.5;
+    float value = 0.0;
+    for (int i = 0; i < 4; i++) {
+        value += shadow_x(in.uv.x, in.uv.y - y, sigma, corner, half_size) * gaussian(y, sigma) * step;
+        y += step;
+    }
+
+    return float4(in.color.rgb, in.color.a * value);
+}";
+
+pub fn box_shadow_meta() -> ShaderMeta {
+    ShaderMeta {
+        images: vec![],
+        uniforms: UniformBlockLayout {
+            uniforms: vec![
+                UniformDesc::new("model", UniformType::Mat4),
+                UniformDesc::new("projection", UniformType::Mat4),
+                UniformDesc::new("shadow", UniformType::Float4),
+            ],
+        },
+    }
+}
+
+#[repr(C)]
+pub struct BoxShadowUniforms {
+    pub model: glam::Mat4,
+    pub projection: glam::Mat4,
+    pub shadow: glam::Vec4,
+}
@@ -0,0 +1,245 @@
+//! Golden-image testing for anything drawn through `DrawContext`.
+//!
+//! A frame is rendered with the `SoftwareBackend` and compared against a stored PNG.
+//! Setting `PORCELAIN_UPDATE_SNAPSHOTS=1` records missing golden images and re-records
+//! existing ones; without it a missing golden image fails the test.
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use miniquad::EventHandler;
+
+use crate::{
+    backend::SoftwareBackend,
+    conf::{IndexFormat, WindowConfig},
+    render::{EventListener, RendererContext},
+};
+
+const UPDATE_ENV_VAR: &str = "PORCELAIN_UPDATE_SNAPSHOTS";
+
+#[derive(Clone)]
+pub struct SnapshotConfig {
+    pub width: u16,
+    pub height: u16,
+    /// Largest per-channel difference still counted as a match.
+    pub tolerance: u8,
+    pub anti_aliasing: bool,
+
+    pub max_vertices_per_draw: usize,
+    pub max_indices_per_draw: usize,
+    pub index_format: IndexFormat,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        let window_config = WindowConfig::default();
+
+        Self {
+            width: 128,
+            height: 128,
+            tolerance: 2,
+            anti_aliasing: window_config.anti_aliasing,
+
+            max_vertices_per_draw: window_config.max_vertices_per_draw,
+            max_indices_per_draw: window_config.max_indices_per_draw,
+            index_format: window_config.index_format,
+        }
+    }
+}
+
+/// RGBA8 pixels of a single rendered frame.
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn load_png(path: &Path) -> Image {
+        let file = File::open(path)
+            .unwrap_or_else(|err| panic!("Cannot open {}: {}", path.display(), err));
+        let mut reader = png::Decoder::new(BufReader::new(file))
+            .read_info()
+            .unwrap_or_else(|err| panic!("Cannot decode {}: {}", path.display(), err));
+
+        let mut pixels = vec![
+            0;
+            reader
+                .output_buffer_size()
+                .expect("Image dimensions must fit in memory")
+        ];
+        let info = reader
+            .next_frame(&mut pixels)
+            .unwrap_or_else(|err| panic!("Cannot decode {}: {}", path.display(), err));
+
+        assert!(
+            info.color_type == png::ColorType::Rgba && info.bit_depth == png::BitDepth::Eight,
+            "Expected {} to be RGBA8, got {:?} {:?}",
+            path.display(),
+            info.color_type,
+            info.bit_depth
+        );
+        pixels.truncate(info.buffer_size());
+
+        Image {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        }
+    }
+
+    pub fn save_png(&self, path: &Path) {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .unwrap_or_else(|err| panic!("Cannot create {}: {}", parent.display(), err));
+        }
+
+        let file = File::create(path)
+            .unwrap_or_else(|err| panic!("Cannot create {}: {}", path.display(), err));
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .unwrap_or_else(|err| panic!("Cannot encode {}: {}", path.display(), err));
+    }
+}
+
+/// Run one `update` and one `draw` of `app_listener` on the software backend.
+///
+/// `update` runs first so listeners can register their textures before drawing.
+pub fn render<T: EventListener + 'static>(config: &SnapshotConfig, app_listener: T) -> Image {
+    let window_config = WindowConfig {
+        window_width: config.width as i32,
+        window_height: config.height as i32,
+        anti_aliasing: config.anti_aliasing,
+        max_vertices_per_draw: config.max_vertices_per_draw,
+        max_indices_per_draw: config.max_indices_per_draw,
+        index_format: config.index_format,
+        ..Default::default()
+    };
+
+    let mut renderer = RendererContext::with_backend(
+        window_config,
+        app_listener,
+        SoftwareBackend::new(config.width, config.height),
+    );
+    renderer.update();
+    renderer.draw();
+
+    let backend = renderer.backend();
+    Image {
+        width: backend.width(),
+        height: backend.height(),
+        pixels: backend.pixels().to_vec(),
+    }
+}
+
+/// Render `app_listener` and compare the frame against the PNG at `golden_path`.
+///
+/// On mismatch, `<name>.actual.png` and `<name>.diff.png` are written next to the
+/// golden image and the call panics. Mismatched pixels are red in the diff image,
+/// matching ones are a dimmed copy of the expected frame. A missing golden image also
+/// panics, after writing `<name>.actual.png`, unless snapshots are being updated.
+pub fn assert_snapshot<T: EventListener + 'static>(
+    golden_path: impl AsRef<Path>,
+    config: &SnapshotConfig,
+    app_listener: T,
+) {
+    let golden_path = golden_path.as_ref();
+    let actual = render(config, app_listener);
+
+    let update = std::env::var_os(UPDATE_ENV_VAR).is_some_and(|value| value != "0");
+    if update {
+        actual.save_png(golden_path);
+        return;
+    }
+
+    let actual_path = sibling_path(golden_path, "actual");
+    if !golden_path.exists() {
+        actual.save_png(&actual_path);
+        panic!(
+            "Snapshot {} is missing; actual frame written to {}, set {}=1 to record it",
+            golden_path.display(),
+            actual_path.display(),
+            UPDATE_ENV_VAR
+        );
+    }
+
+    let expected = Image::load_png(golden_path);
+    let diff_path = sibling_path(golden_path, "diff");
+
+    if expected.width != actual.width || expected.height != actual.height {
+        actual.save_png(&actual_path);
+        panic!(
+            "Snapshot {} is {}x{}, but the frame is {}x{}; actual frame written to {}",
+            golden_path.display(),
+            expected.width,
+            expected.height,
+            actual.width,
+            actual.height,
+            actual_path.display()
+        );
+    }
+
+    let mut mismatched = 0;
+    let mut diff = Vec::with_capacity(expected.pixels.len());
+    for (expected_pixel, actual_pixel) in expected
+        .pixels
+        .chunks_exact(4)
+        .zip(actual.pixels.chunks_exact(4))
+    {
+        let matches = expected_pixel
+            .iter()
+            .zip(actual_pixel)
+            .all(|(a, b)| a.abs_diff(*b) <= config.tolerance);
+
+        if matches {
+            diff.extend(expected_pixel[..3].iter().map(|channel| channel / 4));
+            diff.push(255);
+        } else {
+            mismatched += 1;
+            diff.extend([255, 0, 0, 255]);
+        }
+    }
+
+    if mismatched == 0 {
+        // Clean up leftovers from a previous failing run
+        let _ = std::fs::remove_file(&actual_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return;
+    }
+
+    actual.save_png(&actual_path);
+    Image {
+        width: actual.width,
+        height: actual.height,
+        pixels: diff,
+    }
+    .save_png(&diff_path);
+
+    panic!(
+        "Snapshot {} differs in {} pixels (tolerance {}); see {} and {}, or rerun with {}=1 to accept",
+        golden_path.display(),
+        mismatched,
+        config.tolerance,
+        actual_path.display(),
+        diff_path.display(),
+        UPDATE_ENV_VAR
+    );
+}
+
+fn sibling_path(golden_path: &Path, suffix: &str) -> PathBuf {
+    let stem = golden_path
+        .file_stem()
+        .expect("Snapshot path must name a file")
+        .to_string_lossy();
+
+    golden_path.with_file_name(format!("{}.{}.png", stem, suffix))
+}
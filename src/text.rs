@@ -0,0 +1,323 @@
+//! Font loading and the glyph atlas behind `DrawContext::draw_text`.
+//!
+//! Glyphs are rasterized the first time they are drawn at a given size and packed into
+//! shelves on shared atlas pages, so a run of text usually needs a single texture.
+
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use ab_glyph::{Font, FontArc, GlyphId, PxScale, ScaleFont, point};
+use glam::Vec2;
+use miniquad::{TextureFormat, TextureId};
+
+use crate::{atlas::ShelfPacker, backend::Backend, texture::TextureOptions};
+
+const PAGE_SIZE: u16 = 1024;
+// Empty border around each glyph so linear filtering does not bleed neighbours in.
+const GLYPH_PADDING: u16 = 1;
+
+/// Handle to a font loaded through `TextureContext::load_font`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FontId(usize);
+
+#[derive(Debug)]
+pub struct FontError;
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Font data is not a valid TTF or OTF font")
+    }
+}
+
+impl std::error::Error for FontError {}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextDimensions {
+    pub width: f32,
+    pub height: f32,
+    /// Distance from the top of the text to the baseline of its first line.
+    pub ascent: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: FontId,
+    glyph: GlyphId,
+    size_bits: u32,
+}
+
+#[derive(Clone, Copy)]
+struct CachedGlyph {
+    page: usize,
+    x: u16,
+    y: u16,
+    w: u16,
+    h: u16,
+    // From the pen position on the baseline to the top-left of the bitmap
+    offset: Vec2,
+}
+
+/// Screen rectangle and atlas UVs of one glyph, ready to be turned into a quad.
+pub(crate) struct GlyphQuad {
+    pub(crate) texture: TextureId,
+    pub(crate) position: Vec2,
+    pub(crate) size: Vec2,
+    pub(crate) uv_location: Vec2,
+    pub(crate) uv_size: Vec2,
+}
+
+struct AtlasPage {
+    texture: TextureId,
+    pixels: Vec<u8>,
+    packer: ShelfPacker,
+
+    // (min_x, min_y, max_x, max_y) of pixels not uploaded yet
+    dirty: Option<(u16, u16, u16, u16)>,
+}
+
+pub(crate) struct GlyphCache {
+    backend: Rc<RefCell<dyn Backend>>,
+    fonts: Vec<FontArc>,
+    pages: Vec<AtlasPage>,
+    // `None` for glyphs with nothing to draw, like spaces
+    glyphs: HashMap<GlyphKey, Option<CachedGlyph>>,
+}
+
+impl AtlasPage {
+    fn new(backend: &mut dyn Backend) -> Self {
+        let pixels = vec![0; PAGE_SIZE as usize * PAGE_SIZE as usize * 4];
+
+        Self {
+            texture: backend.new_texture(
+                PAGE_SIZE,
+                PAGE_SIZE,
+                TextureFormat::RGBA8,
+                &pixels,
+                TextureOptions::default(),
+            ),
+            pixels,
+            packer: ShelfPacker::new(PAGE_SIZE, PAGE_SIZE),
+            dirty: None,
+        }
+    }
+
+    fn mark_dirty(&mut self, x: u16, y: u16, w: u16, h: u16) {
+        self.dirty = Some(match self.dirty {
+            Some((min_x, min_y, max_x, max_y)) => (
+                min_x.min(x),
+                min_y.min(y),
+                max_x.max(x + w),
+                max_y.max(y + h),
+            ),
+            None => (x, y, x + w, y + h),
+        });
+    }
+
+    fn flush(&mut self, backend: &mut dyn Backend) {
+        let Some((min_x, min_y, max_x, max_y)) = self.dirty.take() else {
+            return;
+        };
+
+        let mut buffer =
+            Vec::with_capacity((max_x - min_x) as usize * (max_y - min_y) as usize * 4);
+        for y in min_y..max_y {
+            let row = (y as usize * PAGE_SIZE as usize + min_x as usize) * 4;
+            buffer.extend_from_slice(&self.pixels[row..row + (max_x - min_x) as usize * 4]);
+        }
+
+        backend.update_texture_part(
+            self.texture,
+            min_x,
+            min_y,
+            max_x - min_x,
+            max_y - min_y,
+            &buffer,
+        );
+    }
+}
+
+impl GlyphCache {
+    pub(crate) fn new(backend: Rc<RefCell<dyn Backend>>) -> Self {
+        Self {
+            backend,
+            fonts: Vec::new(),
+            pages: Vec::new(),
+            glyphs: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn load_font(&mut self, buffer: &[u8]) -> Result<FontId, FontError> {
+        let font = FontArc::try_from_vec(buffer.to_vec()).map_err(|_| FontError)?;
+        self.fonts.push(font);
+
+        Ok(FontId(self.fonts.len() - 1))
+    }
+
+    fn font(&self, font: FontId) -> &FontArc {
+        self.fonts
+            .get(font.0)
+            .expect("Font was not loaded by this context")
+    }
+
+    pub(crate) fn measure(&self, text: &str, font: FontId, size: f32) -> TextDimensions {
+        let scaled_font = self.font(font).as_scaled(PxScale::from(size));
+        let line_height = scaled_font.height() + scaled_font.line_gap();
+
+        let mut width: f32 = 0.;
+        let mut lines = 0;
+        for line in text.split('\n') {
+            let mut line_width = 0.;
+            let mut previous = None;
+            for c in line.chars() {
+                let glyph = scaled_font.glyph_id(c);
+                if let Some(previous) = previous {
+                    line_width += scaled_font.kern(previous, glyph);
+                }
+                line_width += scaled_font.h_advance(glyph);
+                previous = Some(glyph);
+            }
+
+            width = width.max(line_width);
+            lines += 1;
+        }
+
+        TextDimensions {
+            width,
+            height: (lines - 1) as f32 * line_height + scaled_font.height(),
+            ascent: scaled_font.ascent(),
+        }
+    }
+
+    /// Lay out `text` with the top-left of its first line at `origin`, uploading any
+    /// newly rasterized glyphs before returning.
+    ///
+    /// Glyphs are rasterized at `dpi_scale` physical pixels per unit so they stay sharp on
+    /// high-DPI screens, and their quads are scaled back to logical units.
+    pub(crate) fn layout(
+        &mut self,
+        text: &str,
+        font: FontId,
+        size: f32,
+        dpi_scale: f32,
+        origin: Vec2,
+        quads: &mut Vec<GlyphQuad>,
+    ) {
+        // Cloned so glyphs can be cached while the font is in use; this only bumps a refcount.
+        let font_arc = self.font(font).clone();
+        let scaled_font = font_arc.as_scaled(PxScale::from(size));
+        let line_height = scaled_font.height() + scaled_font.line_gap();
+
+        let mut pen = origin + Vec2::new(0., scaled_font.ascent());
+        let mut previous = None;
+        for c in text.chars() {
+            if c == '\n' {
+                pen = Vec2::new(origin.x, pen.y + line_height);
+                previous = None;
+                continue;
+            }
+
+            let glyph = scaled_font.glyph_id(c);
+            if let Some(previous) = previous {
+                pen.x += scaled_font.kern(previous, glyph);
+            }
+
+            // Snap to whole physical pixels so cached bitmaps are never resampled
+            if let Some(cached) = self.glyph(font, glyph, size * dpi_scale) {
+                let page_size = PAGE_SIZE as f32;
+                quads.push(GlyphQuad {
+                    texture: self.pages[cached.page].texture,
+                    position: ((pen * dpi_scale).round() + cached.offset) / dpi_scale,
+                    size: Vec2::new(cached.w as f32, cached.h as f32) / dpi_scale,
+                    uv_location: Vec2::new(cached.x as f32, cached.y as f32) / page_size,
+                    uv_size: Vec2::new(cached.w as f32, cached.h as f32) / page_size,
+                });
+            }
+
+            pen.x += scaled_font.h_advance(glyph);
+            previous = Some(glyph);
+        }
+
+        let mut backend = self.backend.borrow_mut();
+        for page in &mut self.pages {
+            page.flush(&mut *backend);
+        }
+    }
+
+    /// The glyph rasterized at `size` physical pixels.
+    fn glyph(&mut self, font: FontId, glyph: GlyphId, size: f32) -> Option<CachedGlyph> {
+        let key = GlyphKey {
+            font,
+            glyph,
+            size_bits: size.to_bits(),
+        };
+
+        if let Some(cached) = self.glyphs.get(&key) {
+            return *cached;
+        }
+
+        let cached = self.rasterize(font, glyph, size);
+        self.glyphs.insert(key, cached);
+        cached
+    }
+
+    fn rasterize(&mut self, font: FontId, glyph: GlyphId, size: f32) -> Option<CachedGlyph> {
+        let outline = self
+            .font(font)
+            .outline_glyph(glyph.with_scale_and_position(size, point(0., 0.)))?;
+        let bounds = outline.px_bounds();
+        // Glyphs larger than a whole page are not drawn
+        let w = u16::try_from(bounds.width() as u32).ok()?;
+        let h = u16::try_from(bounds.height() as u32).ok()?;
+        if w == 0 || h == 0 {
+            return None;
+        }
+
+        let padded_w = w.checked_add(GLYPH_PADDING * 2)?;
+        let padded_h = h.checked_add(GLYPH_PADDING * 2)?;
+        if padded_w > PAGE_SIZE || padded_h > PAGE_SIZE {
+            return None;
+        }
+        let (page_index, (x, y)) = match self
+            .pages
+            .last_mut()
+            .and_then(|page| page.packer.allocate(padded_w, padded_h))
+        {
+            Some(location) => (self.pages.len() - 1, location),
+            None => {
+                let mut page = AtlasPage::new(&mut *self.backend.borrow_mut());
+                let location = page
+                    .packer
+                    .allocate(padded_w, padded_h)
+                    .expect("A glyph no larger than a page fits an empty page");
+                self.pages.push(page);
+                (self.pages.len() - 1, location)
+            }
+        };
+        let x = x + GLYPH_PADDING;
+        let y = y + GLYPH_PADDING;
+
+        let page = &mut self.pages[page_index];
+        outline.draw(|glyph_x, glyph_y, coverage| {
+            let index = ((y as usize + glyph_y as usize) * PAGE_SIZE as usize
+                + x as usize
+                + glyph_x as usize)
+                * 4;
+            // White so the vertex color tints the glyph
+            page.pixels[index..index + 4].copy_from_slice(&[
+                255,
+                255,
+                255,
+                (coverage.clamp(0., 1.) * 255.).round() as u8,
+            ]);
+        });
+        page.mark_dirty(x, y, w, h);
+
+        Some(CachedGlyph {
+            page: page_index,
+            x,
+            y,
+            w,
+            h,
+            offset: Vec2::new(bounds.min.x, bounds.min.y),
+        })
+    }
+}
@@ -1,61 +1,374 @@
 use std::{cell::RefCell, rc::Rc};
 
-use miniquad::{RenderingBackend, TextureId, TextureParams};
+use glam::Vec2;
+use miniquad::TextureId;
+pub use miniquad::{FilterMode, TextureFormat, TextureWrap};
+
+#[cfg(feature = "text")]
+use crate::text::{FontId, GlyphCache};
+use crate::{
+    atlas::ImageAtlas,
+    backend::Backend,
+    error::Error,
+    image,
+    material::{Material, MaterialParams},
+    render::TextureArea,
+};
+
+/// How a texture is sampled when drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextureOptions {
+    /// `Nearest` keeps pixel art sharp when scaled up.
+    pub filter: FilterMode,
+    /// What is sampled outside of the 0 to 1 texture coordinates.
+    pub wrap: TextureWrap,
+    /// Smaller copies for drawing the texture scaled down, regenerated on every update.
+    pub mipmaps: bool,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            filter: FilterMode::Linear,
+            wrap: TextureWrap::Clamp,
+            mipmaps: false,
+        }
+    }
+}
+
+/// A texture created by `TextureContext::create_texture`, with its size and pixel format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Texture {
+    pub(crate) id: TextureId,
+    pub(crate) width: u16,
+    pub(crate) height: u16,
+    pub(crate) format: TextureFormat,
+}
+
+impl Texture {
+    pub fn id(&self) -> TextureId {
+        self.id
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// The whole texture, to draw it with `DrawContext::draw_texture`.
+    pub fn texture_area(&self) -> TextureArea {
+        TextureArea {
+            texture: self.id,
+            location: Vec2::ZERO,
+            size: Vec2::ONE,
+        }
+    }
+}
+
+/// Offscreen texture drawn into between `DrawContext::push_render_target` and `pop_render_target`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderTarget {
+    pub(crate) texture: TextureId,
+    pub(crate) width: u16,
+    pub(crate) height: u16,
+}
+
+impl RenderTarget {
+    pub fn texture(&self) -> TextureId {
+        self.texture
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// The whole target, to draw it with `DrawContext::draw_texture`.
+    pub fn texture_area(&self) -> TextureArea {
+        TextureArea {
+            texture: self.texture,
+            location: Vec2::ZERO,
+            size: Vec2::ONE,
+        }
+    }
+}
 
 pub struct TextureContext {
-    backend: Rc<RefCell<Box<dyn RenderingBackend>>>,
+    backend: Rc<RefCell<dyn Backend>>,
+    atlas: RefCell<ImageAtlas>,
+    #[cfg(feature = "text")]
+    glyph_cache: Rc<RefCell<GlyphCache>>,
 }
 
 impl TextureContext {
-    pub(crate) fn new(backend: Rc<RefCell<Box<dyn RenderingBackend>>>) -> Self {
-        Self { backend }
+    pub(crate) fn new(
+        backend: Rc<RefCell<dyn Backend>>,
+        #[cfg(feature = "text")] glyph_cache: Rc<RefCell<GlyphCache>>,
+    ) -> Self {
+        Self {
+            backend,
+            atlas: RefCell::new(ImageAtlas::new()),
+            #[cfg(feature = "text")]
+            glyph_cache,
+        }
+    }
+
+    /// Load a TTF or OTF font for `DrawContext::draw_text`.
+    #[cfg(feature = "text")]
+    pub fn load_font(&self, buffer: &[u8]) -> Result<FontId, Error> {
+        Ok(self.glyph_cache.borrow_mut().load_font(buffer)?)
     }
 
     pub fn register_texture_rgba8(&self, width: u16, height: u16, buffer: &[u8]) -> TextureId {
-        let mut backend_mut = self.backend.borrow_mut();
+        self.try_register_texture_rgba8(width, height, buffer)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
 
-        assert_eq!(
-            width as usize * height as usize * 4,
-            buffer.len(),
-            "Expected {} * {} * 4 = {} bytes, got {} bytes in buffer",
+    pub fn try_register_texture_rgba8(
+        &self,
+        width: u16,
+        height: u16,
+        buffer: &[u8],
+    ) -> Result<TextureId, Error> {
+        let texture = self.try_create_texture(
             width,
             height,
-            width * height * 4,
-            buffer.len()
-        );
-
-        backend_mut.new_texture_from_data_and_format(
+            TextureFormat::RGBA8,
             buffer,
-            TextureParams {
-                width: width as u32,
-                height: height as u32,
-                format: miniquad::TextureFormat::RGBA8,
-                ..Default::default()
-            },
-        )
+            TextureOptions::default(),
+        )?;
+
+        Ok(texture.id)
     }
 
     pub fn register_texture_rgb8(&self, width: u16, height: u16, buffer: &[u8]) -> TextureId {
-        let mut backend_mut = self.backend.borrow_mut();
+        self.try_register_texture_rgb8(width, height, buffer)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
 
-        assert_eq!(
-            width as usize * height as usize * 3,
-            buffer.len(),
-            "Expected {} * {} * 3 = {} bytes, got {} bytes in buffer",
+    pub fn try_register_texture_rgb8(
+        &self,
+        width: u16,
+        height: u16,
+        buffer: &[u8],
+    ) -> Result<TextureId, Error> {
+        let texture = self.try_create_texture(
             width,
             height,
-            width * height * 3,
-            buffer.len()
-        );
-
-        backend_mut.new_texture_from_data_and_format(
+            TextureFormat::RGB8,
             buffer,
-            TextureParams {
-                width: width as u32,
-                height: height as u32,
-                format: miniquad::TextureFormat::RGB8,
-                ..Default::default()
-            },
+            TextureOptions::default(),
+        )?;
+
+        Ok(texture.id)
+    }
+
+    /// A texture filled with `buffer`, in `format`, which can be RGBA8, RGB8 or Alpha.
+    pub fn create_texture(
+        &self,
+        width: u16,
+        height: u16,
+        format: TextureFormat,
+        buffer: &[u8],
+        options: TextureOptions,
+    ) -> Texture {
+        self.try_create_texture(width, height, format, buffer, options)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_create_texture(
+        &self,
+        width: u16,
+        height: u16,
+        format: TextureFormat,
+        buffer: &[u8],
+        options: TextureOptions,
+    ) -> Result<Texture, Error> {
+        self.check_texture_size(width, height)?;
+        check_buffer_size(width, height, format, buffer)?;
+
+        Ok(Texture {
+            id: self
+                .backend
+                .borrow_mut()
+                .new_texture(width, height, format, buffer, options),
+            width,
+            height,
+            format,
+        })
+    }
+
+    /// Replace all the pixels of `texture`, in its format.
+    pub fn update_texture(&self, texture: &Texture, buffer: &[u8]) {
+        self.update_texture_part(texture, 0, 0, texture.width, texture.height, buffer);
+    }
+
+    pub fn try_update_texture(&self, texture: &Texture, buffer: &[u8]) -> Result<(), Error> {
+        self.try_update_texture_part(texture, 0, 0, texture.width, texture.height, buffer)
+    }
+
+    /// Replace the `width` x `height` pixels at (`x`, `y`), e.g. to stream a region that changed.
+    pub fn update_texture_part(
+        &self,
+        texture: &Texture,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        buffer: &[u8],
+    ) {
+        self.try_update_texture_part(texture, x, y, width, height, buffer)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_update_texture_part(
+        &self,
+        texture: &Texture,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+        buffer: &[u8],
+    ) -> Result<(), Error> {
+        if x as u32 + width as u32 > texture.width as u32
+            || y as u32 + height as u32 > texture.height as u32
+        {
+            return Err(Error::TextureOutOfBounds {
+                x,
+                y,
+                width,
+                height,
+                texture_width: texture.width,
+                texture_height: texture.height,
+            });
+        }
+        check_buffer_size(width, height, texture.format, buffer)?;
+
+        self.backend
+            .borrow_mut()
+            .update_texture_part(texture.id, x, y, width, height, buffer);
+        Ok(())
+    }
+
+    /// Free `texture`, which must not be drawn afterwards.
+    pub fn delete_texture(&self, texture: Texture) {
+        self.backend.borrow_mut().delete_texture(texture.id);
+    }
+
+    /// Free `target`, which must not be drawn into or with afterwards.
+    pub fn delete_render_target(&self, target: RenderTarget) {
+        self.backend.borrow_mut().delete_texture(target.texture);
+    }
+
+    /// Compile a material, used between `DrawContext::push_material` and `pop_material`.
+    pub fn create_material(&self, params: &MaterialParams) -> Result<Material, Error> {
+        let id = self.backend.borrow_mut().new_material(params)?;
+
+        Ok(Material::new(id, params.uniforms))
+    }
+
+    /// Decode a PNG, JPEG, QOI or BMP image, recognized from its first bytes, into an RGBA8 texture.
+    pub fn register_texture_from_bytes(&self, bytes: &[u8]) -> Result<Texture, Error> {
+        let image = image::decode(bytes)?;
+
+        self.try_create_texture(
+            image.width,
+            image.height,
+            TextureFormat::RGBA8,
+            &image.pixels,
+            TextureOptions::default(),
         )
     }
+
+    /// Pack an RGBA8 image onto a shared atlas page, so images added this way can be drawn
+    /// in the same draw call. Images too large for a page get a texture of their own.
+    pub fn register_atlas_image_rgba8(
+        &self,
+        width: u16,
+        height: u16,
+        buffer: &[u8],
+    ) -> TextureArea {
+        self.try_register_atlas_image_rgba8(width, height, buffer)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_register_atlas_image_rgba8(
+        &self,
+        width: u16,
+        height: u16,
+        buffer: &[u8],
+    ) -> Result<TextureArea, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::EmptyAtlasImage { width, height });
+        }
+        self.check_texture_size(width, height)?;
+        check_buffer_size(width, height, TextureFormat::RGBA8, buffer)?;
+
+        Ok(self
+            .atlas
+            .borrow_mut()
+            .add(&mut *self.backend.borrow_mut(), width, height, buffer))
+    }
+
+    /// A `width` x `height` pixels texture to draw into, transparent until then.
+    pub fn create_render_target(&self, width: u16, height: u16) -> RenderTarget {
+        self.try_create_render_target(width, height)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_create_render_target(&self, width: u16, height: u16) -> Result<RenderTarget, Error> {
+        self.check_texture_size(width, height)?;
+
+        Ok(RenderTarget {
+            texture: self.backend.borrow_mut().new_render_target(width, height),
+            width,
+            height,
+        })
+    }
+
+    fn check_texture_size(&self, width: u16, height: u16) -> Result<(), Error> {
+        let max_size = self.backend.borrow().max_texture_size();
+        if width as u32 > max_size || height as u32 > max_size {
+            return Err(Error::TextureTooLarge {
+                width,
+                height,
+                max_size,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn check_buffer_size(
+    width: u16,
+    height: u16,
+    format: TextureFormat,
+    buffer: &[u8],
+) -> Result<(), Error> {
+    let bytes_per_pixel = match format {
+        TextureFormat::RGBA8 => 4,
+        TextureFormat::RGB8 => 3,
+        TextureFormat::Alpha => 1,
+        _ => return Err(Error::UnsupportedTextureFormat(format)),
+    };
+
+    if width as usize * height as usize * bytes_per_pixel != buffer.len() {
+        return Err(Error::TextureBufferSize {
+            width,
+            height,
+            bytes_per_pixel,
+            actual: buffer.len(),
+        });
+    }
+    Ok(())
 }
@@ -0,0 +1,91 @@
+//! Frame pacing: how many `update`s each frame runs and with which `dt`, following the
+//! `WindowConfig` timing options.
+//!
+//! `RendererContext` drives a `FrameTimer` with the wall clock. It takes the current time
+//! as an argument so the stepping can be replayed exactly from recorded timestamps.
+//!
+//! `FrameTimer` and `FrameSteps` are public API in their own right, for apps that run a
+//! simulation without `RendererContext`, such as a headless server or a replay tool, and
+//! need the same steps as the window would take.
+
+use crate::conf::WindowConfig;
+
+/// The updates of one frame: `updates` calls with the same `dt`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameSteps {
+    /// Always 1 without a fixed update rate, possibly 0 or several with one.
+    pub updates: u32,
+    /// Seconds each update accounts for.
+    pub dt: f64,
+}
+
+/// Turns frame timestamps into `FrameSteps`, following the `fixed_update_rate`, `max_dt`
+/// and `target_fps` options of a `WindowConfig`.
+///
+/// Call `wait_time` before a frame to pace it, then `tick` with the frame's time to get its
+/// updates, and `alpha` to interpolate what is drawn between the last two fixed updates.
+pub struct FrameTimer {
+    fixed_dt: Option<f64>,
+    max_dt: Option<f64>,
+    target_frame_time: Option<f64>,
+
+    last_time: f64,
+    // Time not yet consumed by fixed updates, always under `fixed_dt`
+    accumulator: f64,
+}
+
+impl FrameTimer {
+    /// A timer whose first frame measures its time from `now`, in seconds.
+    pub fn new(config: &WindowConfig, now: f64) -> Self {
+        Self {
+            fixed_dt: config.fixed_update_rate.map(|rate| 1. / rate),
+            max_dt: config.max_dt,
+            target_frame_time: config.target_fps.map(|fps| 1. / fps),
+            last_time: now,
+            accumulator: 0.,
+        }
+    }
+
+    /// Seconds to wait before starting a frame at `now`, to stay under `target_fps`.
+    pub fn wait_time(&self, now: f64) -> f64 {
+        match self.target_frame_time {
+            Some(frame_time) => (self.last_time + frame_time - now).max(0.),
+            None => 0.,
+        }
+    }
+
+    /// Start a frame at `now`, returning the updates to run for the time elapsed since the
+    /// last one, after clamping it to `max_dt`.
+    pub fn tick(&mut self, now: f64) -> FrameSteps {
+        let mut elapsed = (now - self.last_time).max(0.);
+        if let Some(max_dt) = self.max_dt {
+            elapsed = elapsed.min(max_dt);
+        }
+        self.last_time = now;
+
+        let Some(fixed_dt) = self.fixed_dt else {
+            return FrameSteps {
+                updates: 1,
+                dt: elapsed,
+            };
+        };
+
+        self.accumulator += elapsed;
+        let updates = (self.accumulator / fixed_dt).floor();
+        self.accumulator = (self.accumulator - updates * fixed_dt).max(0.);
+
+        FrameSteps {
+            updates: updates as u32,
+            dt: fixed_dt,
+        }
+    }
+
+    /// How far the time of the last frame is between the last fixed update and the next one,
+    /// from 0 to 1. Always 1 without a fixed update rate, where updates match the frame.
+    pub fn alpha(&self) -> f32 {
+        match self.fixed_dt {
+            Some(fixed_dt) => (self.accumulator / fixed_dt).min(1.) as f32,
+            None => 1.,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::{golden, scene};
+use glam::{Affine2, Vec2};
+use porcelain::{
+    Point,
+    color::Color,
+    render::DrawContext,
+    snapshot::{Image, SnapshotConfig, assert_snapshot, render},
+};
+
+fn anti_aliased() -> SnapshotConfig {
+    SnapshotConfig {
+        anti_aliasing: true,
+        ..Default::default()
+    }
+}
+
+/// Pixels that are neither background nor fully covered by the red shape.
+fn partially_covered(image: &Image) -> usize {
+    image
+        .pixels
+        .chunks_exact(4)
+        .filter(|pixel| pixel[0] != 0 && pixel[0] != 255)
+        .count()
+}
+
+fn circle(draw_context: &mut DrawContext) {
+    draw_context.draw_circle(
+        &Point { x: 64., y: 64. },
+        40.,
+        Color::from_rgba8(255, 0, 0, 255),
+    );
+}
+
+#[test]
+fn shapes() {
+    assert_snapshot(
+        golden("anti_aliasing"),
+        &anti_aliased(),
+        scene(|draw_context| {
+            draw_context.draw_circle(
+                &Point { x: 32., y: 32. },
+                24.,
+                Color::from_rgba8(128, 128, 192, 255),
+            );
+            draw_context.draw_poly_ext(
+                &Point { x: 96., y: 32. },
+                24.,
+                3,
+                10.,
+                Color::from_rgba8(56, 122, 243, 255),
+            );
+            draw_context.draw_rect_ext(
+                &Point { x: 32., y: 96. },
+                36.,
+                20.,
+                25.,
+                Color::from_rgba8(255, 0, 0, 255),
+            );
+            draw_context.draw_circle_arc(
+                &Point { x: 96., y: 96. },
+                24.,
+                0.,
+                270.,
+                Color::from_rgba8(16, 173, 92, 255),
+            );
+            draw_context.draw_line(
+                &Point { x: 8., y: 70. },
+                &Point { x: 120., y: 58. },
+                3.,
+                Color::from_rgba8(255, 255, 255, 255),
+            );
+        }),
+    );
+}
+
+#[test]
+fn edges_are_feathered_only_when_enabled() {
+    assert_eq!(
+        partially_covered(&render(&SnapshotConfig::default(), scene(circle))),
+        0
+    );
+    assert!(partially_covered(&render(&anti_aliased(), scene(circle))) > 100);
+}
+
+#[test]
+fn can_be_switched_per_context() {
+    let image = render(
+        &anti_aliased(),
+        scene(|draw_context| {
+            draw_context.set_anti_aliasing(false);
+            circle(draw_context);
+        }),
+    );
+
+    assert_eq!(partially_covered(&image), 0);
+}
+
+#[test]
+fn pixel_aligned_edges_stay_crisp() {
+    let image = render(
+        &anti_aliased(),
+        scene(|draw_context| {
+            draw_context.draw_rect_ext(
+                &Point { x: 64., y: 64. },
+                40.,
+                20.,
+                0.,
+                Color::from_rgba8(255, 0, 0, 255),
+            );
+        }),
+    );
+
+    assert_eq!(partially_covered(&image), 0);
+}
+
+#[test]
+fn fringe_stays_one_pixel_wide_when_scaled() {
+    let scaled = |draw_context: &mut DrawContext| {
+        draw_context.push_transform(Affine2::from_scale(Vec2::splat(4.)));
+        draw_context.draw_circle(
+            &Point { x: 16., y: 16. },
+            10.,
+            Color::from_rgba8(255, 0, 0, 255),
+        );
+        draw_context.pop_transform();
+    };
+    assert_snapshot(
+        golden("anti_aliasing_scaled"),
+        &anti_aliased(),
+        scene(scaled),
+    );
+
+    // Same circle as drawn without a transform, so the same number of fringe pixels
+    let scaled = partially_covered(&render(&anti_aliased(), scene(scaled)));
+    let unscaled = partially_covered(&render(&anti_aliased(), scene(circle)));
+    assert!(
+        scaled.abs_diff(unscaled) <= unscaled / 10,
+        "{} fringe pixels scaled, {} unscaled",
+        scaled,
+        unscaled
+    );
+}
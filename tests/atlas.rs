@@ -0,0 +1,169 @@
+mod common;
+
+use common::{pixel, scene_with};
+use glam::Vec2;
+use miniquad::EventHandler;
+use porcelain::{
+    backend::SoftwareBackend,
+    color::Color,
+    conf::WindowConfig,
+    render::{DrawContext, RendererContext, TextureArea},
+    snapshot::{Image, SnapshotConfig, render},
+    texture::TextureContext,
+};
+
+/// Adds images to the atlas on update, then draws with the areas it got back.
+fn render_scene(
+    setup: impl Fn(&TextureContext) -> Vec<TextureArea> + 'static,
+    draw: impl Fn(&mut DrawContext, &[TextureArea]) + 'static,
+) -> Image {
+    render(
+        &SnapshotConfig::default(),
+        scene_with(setup, move |draw_context, areas: &Vec<TextureArea>| {
+            draw(draw_context, areas)
+        }),
+    )
+}
+
+fn areas(setup: impl Fn(&TextureContext) -> Vec<TextureArea> + 'static) -> Vec<TextureArea> {
+    let areas = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let captured = areas.clone();
+    render_scene(setup, move |_, drawn| {
+        *captured.borrow_mut() = drawn.to_vec()
+    });
+
+    areas.take()
+}
+
+fn solid(width: u16, height: u16, pixel: [u8; 4]) -> Vec<u8> {
+    pixel.repeat(width as usize * height as usize)
+}
+
+fn white() -> Color {
+    Color::from_rgba8(255, 255, 255, 255)
+}
+
+fn overlaps(a: &TextureArea, b: &TextureArea) -> bool {
+    a.location.x < b.location.x + b.size.x
+        && b.location.x < a.location.x + a.size.x
+        && a.location.y < b.location.y + b.size.y
+        && b.location.y < a.location.y + a.size.y
+}
+
+#[test]
+fn small_images_share_a_page() {
+    let areas = areas(|texture_context| {
+        [(16, 16), (32, 8), (8, 40), (16, 16)]
+            .into_iter()
+            .map(|(w, h)| texture_context.register_atlas_image_rgba8(w, h, &solid(w, h, [255; 4])))
+            .collect()
+    });
+
+    assert!(areas.iter().all(|area| area.texture == areas[0].texture));
+    assert_eq!(areas[0].location, Vec2::splat(1. / 1024.));
+    assert_eq!(areas[1].size, Vec2::new(32., 8.) / 1024.);
+    for (i, a) in areas.iter().enumerate() {
+        for b in &areas[i + 1..] {
+            assert!(!overlaps(a, b), "{:?} overlaps {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn full_pages_spill_onto_new_ones() {
+    // 102 x 102 once padded, so ten rows of ten fill a 1024 x 1024 page
+    let areas = areas(|texture_context| {
+        (0..101)
+            .map(|_| {
+                texture_context.register_atlas_image_rgba8(100, 100, &solid(100, 100, [255; 4]))
+            })
+            .collect()
+    });
+
+    assert!(
+        areas[..100]
+            .iter()
+            .all(|area| area.texture == areas[0].texture)
+    );
+    assert_ne!(areas[100].texture, areas[0].texture);
+    assert_eq!(areas[100].location, Vec2::splat(1. / 1024.));
+}
+
+#[test]
+fn images_larger_than_a_page_get_their_own_texture() {
+    let areas = areas(|texture_context| {
+        vec![
+            texture_context.register_atlas_image_rgba8(8, 8, &solid(8, 8, [255; 4])),
+            texture_context.register_atlas_image_rgba8(2000, 4, &solid(2000, 4, [255; 4])),
+        ]
+    });
+
+    assert_ne!(areas[1].texture, areas[0].texture);
+    assert_eq!(areas[1].location, Vec2::ZERO);
+    assert_eq!(areas[1].size, Vec2::ONE);
+}
+
+#[test]
+fn pages_shrink_to_the_backend_texture_limit() {
+    let areas = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let captured = areas.clone();
+    let mut renderer = RendererContext::with_backend(
+        WindowConfig::default(),
+        scene_with(
+            |texture_context: &TextureContext| {
+                [(8, 8), (250, 250), (255, 4)]
+                    .into_iter()
+                    .map(|(w, h)| {
+                        texture_context.register_atlas_image_rgba8(w, h, &solid(w, h, [255; 4]))
+                    })
+                    .collect::<Vec<_>>()
+            },
+            move |_: &mut DrawContext, drawn: &Vec<TextureArea>| {
+                *captured.borrow_mut() = drawn.clone()
+            },
+        ),
+        SoftwareBackend::new(16, 16).with_max_texture_size(256),
+    );
+    renderer.update();
+    renderer.draw();
+    let areas = areas.take();
+
+    assert_eq!(areas[0].location, Vec2::splat(1. / 256.));
+    assert_eq!(areas[0].size, Vec2::splat(8. / 256.));
+    // Does not fit next to the first image on a 256 x 256 page
+    assert_ne!(areas[1].texture, areas[0].texture);
+    assert_eq!(areas[1].location, Vec2::splat(1. / 256.));
+    // Larger than a page once padded
+    assert_eq!(areas[2].size, Vec2::ONE);
+}
+
+#[test]
+fn neighbours_do_not_bleed_into_scaled_images() {
+    let image = render_scene(
+        |texture_context| {
+            vec![
+                texture_context.register_atlas_image_rgba8(4, 4, &solid(4, 4, [255, 0, 0, 255])),
+                texture_context.register_atlas_image_rgba8(4, 4, &solid(4, 4, [0, 0, 255, 255])),
+            ]
+        },
+        |draw_context, areas| {
+            draw_context.draw_texture(0., 0., 64., 64., areas[0], white());
+            draw_context.draw_texture(64., 0., 64., 64., areas[1], white());
+        },
+    );
+
+    for (x, y, expected) in [
+        (0, 0, [255, 0, 0, 255]),
+        (63, 32, [255, 0, 0, 255]),
+        (64, 32, [0, 0, 255, 255]),
+        (127, 63, [0, 0, 255, 255]),
+    ] {
+        assert_eq!(pixel(&image, x, y), expected, "at ({x}, {y})");
+    }
+}
+
+#[test]
+#[should_panic(expected = "Atlas images cannot be empty")]
+fn empty_images_are_rejected() {
+    areas(|texture_context| vec![texture_context.register_atlas_image_rgba8(0, 4, &[])]);
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::{draw_pixel, snapshot};
+use porcelain::{
+    Point,
+    color::Color,
+    render::{BlendMode, DrawContext},
+};
+
+/// `color` blended with `blend_mode` over an opaque (100, 100, 100) background.
+fn blended(blend_mode: BlendMode, color: Color) -> [u8; 4] {
+    draw_pixel(
+        move |draw_context| {
+            draw_context.draw_rect(0., 0., 16., 16., Color::from_rgba8(100, 100, 100, 255));
+            draw_context.push_blend_mode(blend_mode);
+            draw_context.draw_rect(0., 0., 16., 16., color);
+            draw_context.pop_blend_mode();
+        },
+        8,
+        8,
+    )
+}
+
+#[test]
+fn blend_modes_over_gray() {
+    let opaque = Color::from_rgba8(200, 50, 0, 255);
+    let translucent = Color::from_rgba8(200, 50, 0, 128);
+
+    assert_eq!(blended(BlendMode::Alpha, translucent), [150, 75, 50, 255]);
+    assert_eq!(
+        blended(
+            BlendMode::PremultipliedAlpha,
+            Color::from_rgba8(100, 25, 0, 128)
+        ),
+        [150, 75, 50, 255]
+    );
+    assert_eq!(blended(BlendMode::Additive, opaque), [255, 150, 100, 255]);
+    assert_eq!(blended(BlendMode::Multiply, opaque), [78, 20, 0, 255]);
+    assert_eq!(blended(BlendMode::Screen, opaque), [222, 130, 100, 255]);
+    assert_eq!(blended(BlendMode::Replace, translucent), [200, 50, 0, 128]);
+}
+
+#[test]
+fn translucent_multiply_and_screen_fade_out() {
+    assert_eq!(
+        blended(BlendMode::Multiply, Color::from_rgba8(0, 0, 0, 0)),
+        [100, 100, 100, 255]
+    );
+    assert_eq!(
+        blended(BlendMode::Multiply, Color::from_rgba8(0, 0, 0, 128)),
+        [50, 50, 50, 255]
+    );
+    assert_eq!(
+        blended(BlendMode::Screen, Color::from_rgba8(255, 255, 255, 0)),
+        [100, 100, 100, 255]
+    );
+}
+
+#[test]
+fn blend_mode_is_restored_after_pop() {
+    let draw = |draw_context: &mut DrawContext| {
+        assert_eq!(draw_context.blend_mode(), BlendMode::Alpha);
+        draw_context.push_blend_mode(BlendMode::Additive);
+        draw_context.push_blend_mode(BlendMode::Multiply);
+        assert_eq!(draw_context.blend_mode(), BlendMode::Multiply);
+        draw_context.pop_blend_mode();
+        assert_eq!(draw_context.blend_mode(), BlendMode::Additive);
+        draw_context.pop_blend_mode();
+
+        draw_context.draw_rect(0., 0., 16., 16., Color::from_rgba8(255, 0, 0, 128));
+    };
+
+    assert_eq!(draw_pixel(draw, 8, 8), [128, 0, 0, 255]);
+}
+
+#[test]
+#[should_panic(expected = "pop_blend_mode called without a matching push_blend_mode")]
+fn unbalanced_pop_blend_mode_panics() {
+    draw_pixel(|draw_context| draw_context.pop_blend_mode(), 0, 0);
+}
+
+#[test]
+fn overlapping_circles() {
+    snapshot("blend_modes", |draw_context| {
+        draw_context.draw_rect(0., 0., 128., 128., Color::from_rgba8(60, 60, 70, 255));
+        draw_context.draw_rect(64., 0., 64., 128., Color::from_rgba8(220, 220, 210, 255));
+
+        let modes = [
+            (BlendMode::Additive, 32.),
+            (BlendMode::Screen, 64.),
+            (BlendMode::Multiply, 96.),
+        ];
+        for (blend_mode, y) in modes {
+            draw_context.push_blend_mode(blend_mode);
+            for (x, color) in [
+                (52., Color::from_rgba8(230, 40, 40, 200)),
+                (64., Color::from_rgba8(40, 200, 60, 200)),
+                (76., Color::from_rgba8(50, 80, 230, 200)),
+            ] {
+                draw_context.draw_circle(&Point { x, y }, 14., color);
+            }
+            draw_context.pop_blend_mode();
+        }
+    });
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::{draw_pixel, snapshot};
+use porcelain::{
+    color::Color,
+    layout::{ElementConfig, Layout, Padding, Sizing, SizingAxis},
+    render::DrawContext,
+};
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+#[test]
+fn nested_clips_intersect() {
+    snapshot("clip_nested", |draw_context| {
+        draw_context.push_clip_rect(16., 16., 64., 64.);
+        draw_context.draw_circle(
+            &porcelain::Point { x: 48., y: 48. },
+            48.,
+            Color::from_rgba8(0, 160, 255, 255),
+        );
+
+        draw_context.push_clip_rect(48., 48., 64., 64.);
+        draw_context.draw_rect(0., 0., 128., 128., Color::from_rgba8(255, 96, 0, 255));
+        draw_context.pop_clip_rect();
+
+        // Back to the outer clip only
+        draw_context.draw_rect(0., 0., 24., 128., Color::from_rgba8(0, 200, 80, 255));
+        draw_context.pop_clip_rect();
+
+        draw_context.draw_rect(100., 100., 20., 20., Color::from_rgba8(255, 255, 255, 255));
+    });
+}
+
+#[test]
+fn clip_only_applies_until_popped() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.push_clip_rect(0., 0., 10., 10.);
+        draw_context.draw_rect(0., 0., 50., 50., Color::from_rgba8(255, 0, 0, 255));
+        draw_context.pop_clip_rect();
+        draw_context.draw_rect(60., 60., 10., 10., Color::from_rgba8(255, 0, 0, 255));
+    };
+
+    assert_eq!(draw_pixel(draw, 5, 5), RED);
+    assert_eq!(draw_pixel(draw, 20, 20), BLACK);
+    assert_eq!(draw_pixel(draw, 65, 65), RED);
+}
+
+#[test]
+fn disjoint_clips_draw_nothing() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.push_clip_rect(0., 0., 10., 10.);
+        draw_context.push_clip_rect(20., 20., 10., 10.);
+        draw_context.draw_rect(0., 0., 50., 50., Color::from_rgba8(255, 0, 0, 255));
+        draw_context.pop_clip_rect();
+        draw_context.pop_clip_rect();
+    };
+
+    assert_eq!(draw_pixel(draw, 5, 5), BLACK);
+    assert_eq!(draw_pixel(draw, 25, 25), BLACK);
+}
+
+#[test]
+fn layout_clips_children() {
+    snapshot("clip_layout", |draw_context| {
+        let mut layout = Layout::new(128., 128.);
+        layout.element(
+            ElementConfig {
+                sizing: Sizing {
+                    width: SizingAxis::Fixed(64.),
+                    height: SizingAxis::Fixed(64.),
+                },
+                padding: Padding::all(8.),
+                background: Some(Color::from_rgba8(60, 60, 90, 255)),
+                clip: true,
+                ..Default::default()
+            },
+            |layout| {
+                layout.leaf(ElementConfig {
+                    sizing: Sizing {
+                        width: SizingAxis::Fixed(100.),
+                        height: SizingAxis::Fixed(30.),
+                    },
+                    background: Some(Color::from_rgba8(240, 200, 60, 255)),
+                    ..Default::default()
+                });
+            },
+        );
+        layout.end(draw_context);
+    });
+}
+
+#[test]
+#[should_panic(expected = "without a matching push_clip_rect")]
+fn unbalanced_pop_panics() {
+    draw_pixel(|draw_context| draw_context.pop_clip_rect(), 0, 0);
+}
@@ -0,0 +1,82 @@
+//! Scenes and snapshot helpers shared by the integration tests, included with `mod common;`.
+
+// Each test crate only uses some of the helpers
+#![allow(dead_code)]
+
+use porcelain::{
+    render::{DrawContext, EventListener},
+    snapshot::{Image, SnapshotConfig, assert_snapshot, render},
+    texture::TextureContext,
+};
+
+/// Runs `setup` on the first update and draws with what it returned, so textures, render
+/// targets or materials exist before the first frame.
+pub struct Scene<T, S, D> {
+    setup: S,
+    draw: D,
+    state: Option<T>,
+}
+
+impl<T, S, D> EventListener for Scene<T, S, D>
+where
+    S: Fn(&TextureContext) -> T,
+    D: Fn(&mut DrawContext, &T),
+{
+    fn update(&mut self, texture_context: &TextureContext, _dt: f64) {
+        if self.state.is_none() {
+            self.state = Some((self.setup)(texture_context));
+        }
+    }
+
+    fn draw(&self, draw_context: &mut DrawContext) {
+        (self.draw)(
+            draw_context,
+            self.state.as_ref().expect("Update runs before draw"),
+        );
+    }
+}
+
+pub fn scene_with<T, S, D>(setup: S, draw: D) -> Scene<T, S, D>
+where
+    S: Fn(&TextureContext) -> T,
+    D: Fn(&mut DrawContext, &T),
+{
+    Scene {
+        setup,
+        draw,
+        state: None,
+    }
+}
+
+/// A scene that only draws.
+pub fn scene(draw: impl Fn(&mut DrawContext) + 'static) -> impl EventListener + 'static {
+    scene_with(
+        |_: &TextureContext| (),
+        move |draw_context: &mut DrawContext, _: &()| draw(draw_context),
+    )
+}
+
+/// Path of the golden image `name` in `tests/snapshots`.
+pub fn golden(name: &str) -> String {
+    format!(
+        "{}/tests/snapshots/{}.png",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    )
+}
+
+/// Compare what `draw` draws against the golden image `name`.
+pub fn snapshot(name: &str, draw: impl Fn(&mut DrawContext) + 'static) {
+    assert_snapshot(golden(name), &SnapshotConfig::default(), scene(draw));
+}
+
+pub fn pixel(image: &Image, x: usize, y: usize) -> [u8; 4] {
+    let index = (y * image.width + x) * 4;
+
+    image.pixels[index..index + 4].try_into().unwrap()
+}
+
+/// The pixel at (`x`, `y`) of a frame drawn by `draw`.
+pub fn draw_pixel(draw: impl Fn(&mut DrawContext) + 'static, x: usize, y: usize) -> [u8; 4] {
+    pixel(&render(&SnapshotConfig::default(), scene(draw)), x, y)
+}
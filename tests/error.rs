@@ -0,0 +1,390 @@
+mod common;
+
+use std::error::Error as _;
+
+use common::{draw_pixel, scene, scene_with};
+use glam::Vec2;
+use miniquad::EventHandler;
+use porcelain::{
+    Error, Point, Rect,
+    backend::{Backend, SoftwareBackend},
+    color::Color,
+    conf::WindowConfig,
+    gradient::Gradient,
+    image::{self, DecodedImage, ImageError},
+    render::{CornerRadii, DrawContext, RendererContext},
+    snapshot::{SnapshotConfig, render},
+    texture::{RenderTarget, TextureContext, TextureFormat, TextureOptions},
+};
+
+/// Runs `setup` with the texture context on update.
+fn with_texture_context(setup: impl Fn(&TextureContext) + 'static) {
+    render(
+        &SnapshotConfig::default(),
+        scene_with(setup, |_: &mut DrawContext, _: &()| {}),
+    );
+}
+
+fn try_renderer(config: WindowConfig) -> Result<(), Error> {
+    RendererContext::try_with_backend(config, scene(|_| {}), SoftwareBackend::new(16, 16))
+        .map(|_| ())
+}
+
+/// Runs `setup` with the texture context of a backend limited to 64 pixel wide textures.
+fn with_small_textures(setup: impl Fn(&TextureContext) + 'static) {
+    let mut renderer = RendererContext::with_backend(
+        WindowConfig::default(),
+        scene_with(setup, |_: &mut DrawContext, _: &()| {}),
+        SoftwareBackend::new(16, 16).with_max_texture_size(64),
+    );
+    renderer.update();
+}
+
+fn decode(bytes: &[u8]) -> Result<DecodedImage, Error> {
+    Ok(image::decode(bytes)?)
+}
+
+#[test]
+fn draw_limits_must_fit_a_quad() {
+    let error = try_renderer(WindowConfig {
+        max_vertices_per_draw: 4,
+        ..Default::default()
+    })
+    .unwrap_err();
+
+    assert!(matches!(error, Error::InvalidConfig(_)));
+    assert_eq!(
+        error.to_string(),
+        "Invalid window config: draws must fit at least a quad, over 4 vertices and 6 indices, got 4 and 30000"
+    );
+}
+
+#[test]
+fn window_size_must_be_positive() {
+    let config = WindowConfig {
+        window_width: 0,
+        ..Default::default()
+    };
+
+    assert!(matches!(config.validate(), Err(Error::InvalidConfig(_))));
+    assert!(WindowConfig::default().validate().is_ok());
+}
+
+#[test]
+#[should_panic(expected = "Invalid window config: sample_count must be at least 1, got 0")]
+fn with_backend_panics_with_the_error() {
+    RendererContext::with_backend(
+        WindowConfig {
+            sample_count: 0,
+            ..Default::default()
+        },
+        scene(|_| {}),
+        SoftwareBackend::new(16, 16),
+    );
+}
+
+#[test]
+fn smallest_draw_limits_still_render() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.draw_poly(
+            &Point { x: 64., y: 64. },
+            48.,
+            200,
+            Color::from_rgba8(255, 255, 255, 255),
+        );
+        draw_context.draw_circle_arc(
+            &Point { x: 64., y: 64. },
+            24.,
+            0.,
+            270.,
+            Color::from_rgba8(255, 0, 0, 255),
+        );
+    };
+    let expected = render(&SnapshotConfig::default(), scene(draw));
+    let tiny = render(
+        &SnapshotConfig {
+            max_vertices_per_draw: 5,
+            max_indices_per_draw: 7,
+            ..Default::default()
+        },
+        scene(draw),
+    );
+
+    assert!(expected.pixels == tiny.pixels);
+}
+
+#[test]
+fn texture_buffer_size_is_an_error() {
+    with_texture_context(|texture_context| {
+        let error = texture_context
+            .try_register_texture_rgba8(2, 2, &[0; 12])
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::TextureBufferSize {
+                width: 2,
+                height: 2,
+                bytes_per_pixel: 4,
+                actual: 12
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Expected 2 * 2 * 4 = 16 bytes, got 12 bytes in buffer"
+        );
+        assert!(
+            texture_context
+                .try_register_texture_rgb8(2, 2, &[0; 12])
+                .is_ok()
+        );
+    });
+}
+
+#[test]
+fn unsupported_texture_format_is_an_error() {
+    with_texture_context(|texture_context| {
+        let error = texture_context
+            .try_create_texture(
+                2,
+                2,
+                TextureFormat::Depth,
+                &[0; 16],
+                TextureOptions::default(),
+            )
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::UnsupportedTextureFormat(TextureFormat::Depth)
+        ));
+    });
+}
+
+#[test]
+fn texture_update_out_of_bounds_is_an_error() {
+    with_texture_context(|texture_context| {
+        let texture = texture_context.create_texture(
+            4,
+            4,
+            TextureFormat::RGBA8,
+            &[0; 64],
+            TextureOptions::default(),
+        );
+
+        let error = texture_context
+            .try_update_texture_part(&texture, 3, 0, 2, 1, &[0; 8])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Area 2 * 1 at (3, 0) is outside of the 4 * 4 texture"
+        );
+
+        assert!(matches!(
+            texture_context.try_update_texture(&texture, &[0; 60]),
+            Err(Error::TextureBufferSize { actual: 60, .. })
+        ));
+        assert!(
+            texture_context
+                .try_update_texture(&texture, &[0; 64])
+                .is_ok()
+        );
+    });
+}
+
+#[test]
+fn module_errors_convert_with_the_question_mark() {
+    with_texture_context(|texture_context| {
+        let error = decode(b"not an image").unwrap_err();
+
+        assert!(matches!(error, Error::Image(ImageError::UnknownFormat)));
+        assert!(error.source().is_some());
+        assert_eq!(error.to_string(), ImageError::UnknownFormat.to_string());
+
+        assert!(matches!(
+            texture_context.register_texture_from_bytes(b"not an image"),
+            Err(Error::Image(ImageError::UnknownFormat))
+        ));
+        #[cfg(feature = "text")]
+        assert!(matches!(
+            texture_context.load_font(&[0, 1, 2, 3]),
+            Err(Error::Font(_))
+        ));
+    });
+}
+
+#[test]
+fn textures_over_the_backend_limit_are_an_error() {
+    with_small_textures(|texture_context| {
+        let error = texture_context
+            .try_create_texture(
+                65,
+                1,
+                TextureFormat::RGBA8,
+                &[0; 260],
+                TextureOptions::default(),
+            )
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::TextureTooLarge {
+                width: 65,
+                height: 1,
+                max_size: 64
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Texture of 65 * 1 is larger than the 64 * 64 the backend supports"
+        );
+
+        assert!(matches!(
+            texture_context.try_create_render_target(16, 100),
+            Err(Error::TextureTooLarge { height: 100, .. })
+        ));
+        assert!(matches!(
+            texture_context.try_register_atlas_image_rgba8(80, 2, &[0; 640]),
+            Err(Error::TextureTooLarge { width: 80, .. })
+        ));
+        assert!(texture_context.try_create_render_target(64, 64).is_ok());
+    });
+}
+
+#[test]
+fn invalid_atlas_images_are_an_error() {
+    with_texture_context(|texture_context| {
+        let error = texture_context
+            .try_register_atlas_image_rgba8(0, 4, &[])
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::EmptyAtlasImage {
+                width: 0,
+                height: 4
+            }
+        ));
+        assert_eq!(error.to_string(), "Atlas images cannot be empty, got 0 * 4");
+
+        assert!(matches!(
+            texture_context.try_register_atlas_image_rgba8(2, 2, &[0; 12]),
+            Err(Error::TextureBufferSize { actual: 12, .. })
+        ));
+        assert!(
+            texture_context
+                .try_register_atlas_image_rgba8(2, 2, &[0; 16])
+                .is_ok()
+        );
+    });
+}
+
+#[test]
+fn gradient_without_stops_is_an_error() {
+    let empty = Gradient::linear(Vec2::ZERO, Vec2::X, &[]);
+
+    assert!(matches!(empty.validate(), Err(Error::EmptyGradient)));
+    assert_eq!(
+        empty.validate().unwrap_err().to_string(),
+        "A gradient needs at least one color stop"
+    );
+
+    let pixel = draw_pixel(
+        move |draw_context| {
+            let rect = Rect {
+                x: 0.,
+                y: 0.,
+                w: 8.,
+                h: 8.,
+            };
+            assert!(matches!(
+                draw_context.try_draw_rect_gradient(0., 0., 8., 8., &empty),
+                Err(Error::EmptyGradient)
+            ));
+            assert!(
+                draw_context
+                    .try_draw_rounded_rect_gradient(rect, CornerRadii::all(2.), &empty)
+                    .is_err()
+            );
+            assert!(
+                draw_context
+                    .try_draw_circle_gradient(&Point { x: 4., y: 4. }, 4., &empty)
+                    .is_err()
+            );
+        },
+        4,
+        4,
+    );
+    // Nothing was drawn
+    assert_eq!(pixel, [0, 0, 0, 255]);
+}
+
+#[test]
+fn drawing_a_target_into_itself_is_an_error() {
+    render(
+        &SnapshotConfig::default(),
+        scene_with(
+            |texture_context: &TextureContext| texture_context.create_render_target(8, 8),
+            |draw_context: &mut DrawContext, target: &RenderTarget| {
+                draw_context.push_render_target(*target, None);
+                let error = draw_context
+                    .try_draw_texture(
+                        0.,
+                        0.,
+                        8.,
+                        8.,
+                        target.texture_area(),
+                        Color::from_rgba8(255, 255, 255, 255),
+                    )
+                    .unwrap_err();
+                assert!(matches!(error, Error::RenderTargetDrawnIntoItself));
+                assert_eq!(
+                    error.to_string(),
+                    "A render target cannot be drawn into itself"
+                );
+                draw_context.pop_render_target();
+
+                assert!(
+                    draw_context
+                        .try_draw_texture(
+                            0.,
+                            0.,
+                            8.,
+                            8.,
+                            target.texture_area(),
+                            Color::from_rgba8(255, 255, 255, 255)
+                        )
+                        .is_ok()
+                );
+            },
+        ),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Depth textures cannot be created from pixels")]
+fn software_backend_rejects_unsupported_formats() {
+    SoftwareBackend::new(4, 4).new_texture(
+        1,
+        1,
+        TextureFormat::Depth,
+        &[0; 4],
+        TextureOptions::default(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "A gradient needs at least one color stop")]
+fn drawing_a_gradient_without_stops_panics() {
+    draw_pixel(
+        |draw_context| {
+            draw_context.draw_rect_gradient(
+                0.,
+                0.,
+                8.,
+                8.,
+                &Gradient::linear(Vec2::ZERO, Vec2::X, &[]),
+            )
+        },
+        0,
+        0,
+    );
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::{draw_pixel, snapshot};
+use glam::Vec2;
+use porcelain::{
+    Point, Rect,
+    color::Color,
+    gradient::{ColorStop, Gradient},
+    path::{FillRule, Path},
+    render::{CornerRadii, DrawContext},
+};
+
+fn assert_close(actual: [u8; 4], expected: [u8; 4]) {
+    assert!(
+        actual
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| actual.abs_diff(expected) <= 2),
+        "{actual:?} is not close to {expected:?}"
+    );
+}
+
+/// Red, then green halfway, then blue.
+fn stops() -> Vec<ColorStop> {
+    vec![
+        ColorStop::new(0., Color::from_rgba8(255, 0, 0, 255)),
+        ColorStop::new(0.5, Color::from_rgba8(0, 255, 0, 255)),
+        ColorStop::new(1., Color::from_rgba8(0, 0, 255, 255)),
+    ]
+}
+
+fn star(x: f32, y: f32, radius: f32) -> Path {
+    let mut path = Path::new();
+    for i in 0..5 {
+        let angle = (i as f32 * 144. - 90.).to_radians();
+        path.line_to(x + radius * angle.cos(), y + radius * angle.sin());
+    }
+    path.close();
+    path
+}
+
+#[test]
+fn gradient_fills() {
+    snapshot("gradient", |draw_context| {
+        draw_context.draw_rect_gradient(
+            8.,
+            8.,
+            48.,
+            48.,
+            &Gradient::linear(Vec2::new(8., 8.), Vec2::new(56., 56.), &stops()),
+        );
+        draw_context.draw_circle_gradient(
+            &Point { x: 96., y: 32. },
+            24.,
+            &Gradient::radial(Vec2::new(96., 32.), 24., &stops()),
+        );
+        draw_context.draw_circle_gradient(
+            &Point { x: 32., y: 96. },
+            24.,
+            &Gradient::conic(Vec2::new(32., 96.), 0., &stops()),
+        );
+        draw_context.draw_rounded_rect_gradient(
+            Rect {
+                x: 72.,
+                y: 72.,
+                w: 48.,
+                h: 48.,
+            },
+            CornerRadii::all(12.),
+            &Gradient::linear(Vec2::new(72., 0.), Vec2::new(120., 0.), &stops()),
+        );
+        draw_context.draw_path_gradient(
+            &star(96., 96., 16.),
+            FillRule::EvenOdd,
+            &Gradient::radial(
+                Vec2::new(96., 96.),
+                16.,
+                &[
+                    ColorStop::new(0., Color::from_rgba8(255, 255, 255, 255)),
+                    ColorStop::new(1., Color::from_rgba8(0, 0, 0, 255)),
+                ],
+            ),
+        );
+    });
+}
+
+#[test]
+fn linear_gradients_hit_every_stop() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.draw_rect_gradient(
+            0.,
+            0.,
+            128.,
+            128.,
+            &Gradient::linear(Vec2::new(0.5, 0.), Vec2::new(128.5, 0.), &stops()),
+        );
+    };
+
+    // Pixel centers at 0.25, 0.5 and 0.75 along the gradient
+    assert_close(draw_pixel(draw, 32, 64), [128, 128, 0, 255]);
+    assert_close(draw_pixel(draw, 64, 10), [0, 255, 0, 255]);
+    assert_close(draw_pixel(draw, 96, 64), [0, 128, 128, 255]);
+}
+
+#[test]
+fn radial_gradients_follow_the_distance() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.draw_rect_gradient(
+            0.,
+            0.,
+            128.,
+            128.,
+            &Gradient::radial(Vec2::new(64.5, 64.5), 40., &stops()),
+        );
+    };
+
+    // Pixel centers 10, 30 and 44.5 away from the center
+    assert_close(draw_pixel(draw, 64, 74), [128, 128, 0, 255]);
+    assert_close(draw_pixel(draw, 64, 34), [0, 128, 128, 255]);
+    assert_close(draw_pixel(draw, 96, 96), [0, 0, 255, 255]);
+}
+
+#[test]
+fn radial_hard_stops() {
+    let rings = [
+        ColorStop::new(0., Color::from_rgba8(255, 0, 0, 255)),
+        ColorStop::new(0.25, Color::from_rgba8(255, 0, 0, 255)),
+        ColorStop::new(0.25, Color::from_rgba8(255, 255, 255, 255)),
+        ColorStop::new(0.5, Color::from_rgba8(255, 255, 255, 255)),
+        ColorStop::new(0.5, Color::from_rgba8(0, 0, 255, 255)),
+        ColorStop::new(1., Color::from_rgba8(0, 0, 255, 255)),
+    ];
+    snapshot("gradient_radial_hard_stops", move |draw_context| {
+        draw_context.draw_rect_gradient(
+            0.,
+            0.,
+            128.,
+            128.,
+            &Gradient::radial(Vec2::new(50., 70.), 96., &rings),
+        );
+    });
+}
+
+#[test]
+fn conic_gradients_turn_clockwise_from_the_top() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.draw_rect_gradient(
+            0.,
+            0.,
+            128.,
+            128.,
+            &Gradient::conic(Vec2::new(64.5, 64.5), 0., &stops()),
+        );
+    };
+
+    // A quarter and three quarters of a turn, then just either side of the start at the top
+    assert_close(draw_pixel(draw, 100, 64), [128, 128, 0, 255]);
+    assert_close(draw_pixel(draw, 28, 64), [0, 128, 128, 255]);
+    assert_close(draw_pixel(draw, 65, 10), [255, 0, 0, 255]);
+    assert_close(draw_pixel(draw, 63, 10), [0, 0, 255, 255]);
+}
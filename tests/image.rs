@@ -0,0 +1,231 @@
+mod common;
+
+use common::{pixel, scene_with};
+use porcelain::{
+    color::Color,
+    image::{DecodedImage, ImageError, ImageFormat, decode},
+    render::{DrawContext, TextureArea},
+    snapshot::{SnapshotConfig, render},
+    texture::TextureContext,
+};
+
+// Same 4x4 pattern in every lossless fixture
+const PATTERN: [[[u8; 4]; 4]; 4] = [
+    [
+        [255, 0, 0, 255],
+        [0, 255, 0, 255],
+        [0, 0, 255, 255],
+        [255, 255, 255, 255],
+    ],
+    [
+        [0, 0, 0, 255],
+        [255, 255, 0, 128],
+        [0, 255, 255, 64],
+        [255, 0, 255, 0],
+    ],
+    [
+        [10, 20, 30, 255],
+        [40, 50, 60, 255],
+        [70, 80, 90, 255],
+        [100, 110, 120, 255],
+    ],
+    [
+        [200, 100, 0, 255],
+        [0, 100, 200, 255],
+        [128, 128, 128, 255],
+        [1, 2, 3, 255],
+    ],
+];
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = format!("{}/tests/images/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read(&path).unwrap_or_else(|err| panic!("Cannot read {}: {}", path, err))
+}
+
+fn pattern(opaque: bool) -> Vec<u8> {
+    PATTERN
+        .as_flattened()
+        .iter()
+        .flat_map(|&[r, g, b, a]| [r, g, b, if opaque { 255 } else { a }])
+        .collect()
+}
+
+fn assert_close(actual: &[u8], expected: [u8; 4]) {
+    assert!(
+        actual
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| actual.abs_diff(expected) <= 2),
+        "{:?} is not close to {:?}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn formats_are_sniffed_from_signatures() {
+    assert_eq!(
+        ImageFormat::sniff(&fixture("rgba.png")),
+        Some(ImageFormat::Png)
+    );
+    assert_eq!(
+        ImageFormat::sniff(&fixture("color.jpg")),
+        Some(ImageFormat::Jpeg)
+    );
+    assert_eq!(
+        ImageFormat::sniff(&fixture("rgba.qoi")),
+        Some(ImageFormat::Qoi)
+    );
+    assert_eq!(
+        ImageFormat::sniff(&fixture("rgb24.bmp")),
+        Some(ImageFormat::Bmp)
+    );
+    assert_eq!(ImageFormat::sniff(b"GIF89a"), None);
+}
+
+#[test]
+fn lossless_formats_decode_to_rgba8() {
+    for (name, opaque) in [
+        ("rgba.png", false),
+        ("rgba.qoi", false),
+        ("rgba32.bmp", false),
+        ("rgb24.bmp", true),
+        ("palette8.bmp", true),
+    ] {
+        let image = decode(&fixture(name)).unwrap();
+        assert_eq!(
+            image,
+            DecodedImage {
+                width: 4,
+                height: 4,
+                pixels: pattern(opaque),
+            },
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn gray_png_is_expanded() {
+    let image = decode(&fixture("gray.png")).unwrap();
+
+    assert_eq!(&image.pixels[..4], [255, 255, 255, 255]);
+    assert_eq!(&image.pixels[4..8], [0, 0, 0, 255]);
+    assert_eq!(&image.pixels[8 * 4..9 * 4], [10, 10, 10, 255]);
+}
+
+#[test]
+fn jpeg_decodes_color_and_gray() {
+    let color = decode(&fixture("color.jpg")).unwrap();
+    assert_eq!((color.width, color.height), (16, 8));
+    assert_close(&color.pixels[..4], [200, 40, 40, 255]);
+    assert_close(&color.pixels[(8 * 16 - 1) * 4..], [40, 80, 200, 255]);
+
+    let gray = decode(&fixture("gray.jpg")).unwrap();
+    assert_close(&gray.pixels[..4], [50, 50, 50, 255]);
+    assert_close(&gray.pixels[15 * 4..16 * 4], [220, 220, 220, 255]);
+}
+
+#[test]
+fn unknown_and_corrupted_data_are_errors() {
+    assert!(matches!(
+        decode(b"not an image"),
+        Err(ImageError::UnknownFormat)
+    ));
+
+    for name in ["rgba.png", "color.jpg", "rgba.qoi", "rgb24.bmp"] {
+        let bytes = fixture(name);
+        let format = ImageFormat::sniff(&bytes).unwrap();
+        match decode(&bytes[..bytes.len() / 2]) {
+            Err(ImageError::Decode {
+                format: error_format,
+                ..
+            }) => assert_eq!(error_format, format),
+            other => panic!("{} truncated gave {:?}", name, other),
+        }
+    }
+}
+
+// BITMAPV3INFOHEADER with the masks inside the header, pixel data right after it
+fn bmp(width: i32, height: i32, bits_per_pixel: u16, masks: [u32; 4], data: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"BM");
+    bytes.extend_from_slice(&(70 + data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes.extend_from_slice(&70u32.to_le_bytes());
+    bytes.extend_from_slice(&56u32.to_le_bytes());
+    bytes.extend_from_slice(&width.to_le_bytes());
+    bytes.extend_from_slice(&height.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&bits_per_pixel.to_le_bytes());
+    // BI_BITFIELDS
+    bytes.extend_from_slice(&3u32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 20]);
+    for mask in masks {
+        bytes.extend_from_slice(&mask.to_le_bytes());
+    }
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+#[test]
+fn oversized_bmp_dimensions_are_errors() {
+    for (width, height) in [(i32::MAX, i32::MAX), (i32::MAX, -i32::MAX), (1, i32::MIN)] {
+        let bytes = bmp(width, height, 32, [0xff, 0xff00, 0xff_0000, 0], &[0; 4]);
+        assert!(
+            matches!(
+                decode(&bytes),
+                Err(ImageError::Decode {
+                    format: ImageFormat::Bmp,
+                    ..
+                })
+            ),
+            "{} * {} did not fail",
+            width,
+            height
+        );
+    }
+}
+
+#[test]
+fn full_width_bmp_masks_are_scaled() {
+    let masks = [u32::MAX, 0xffff_0000, 0x0000_ffff, 0];
+    let image = decode(&bmp(1, 1, 32, masks, &0xffff_0000u32.to_le_bytes())).unwrap();
+
+    assert_eq!(
+        image,
+        DecodedImage {
+            width: 1,
+            height: 1,
+            pixels: vec![254, 255, 0, 255],
+        }
+    );
+}
+
+#[test]
+fn decoded_images_become_textures() {
+    let scene = scene_with(
+        |texture_context: &TextureContext| {
+            texture_context
+                .register_texture_from_bytes(&fixture("rgba.png"))
+                .unwrap()
+                .texture_area()
+        },
+        |draw_context: &mut DrawContext, texture: &TextureArea| {
+            draw_context.draw_texture(
+                0.,
+                0.,
+                64.,
+                64.,
+                *texture,
+                Color::from_rgba8(255, 255, 255, 255),
+            );
+        },
+    );
+    let image = render(&SnapshotConfig::default(), scene);
+
+    assert_eq!(pixel(&image, 2, 2), [255, 0, 0, 255]);
+    assert_eq!(pixel(&image, 61, 2), [255, 255, 255, 255]);
+    assert_eq!(pixel(&image, 61, 61), [1, 2, 3, 255]);
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use std::f32::consts::TAU;
+
+use common::{pixel, scene};
+use porcelain::{
+    Point,
+    color::Color,
+    conf::IndexFormat,
+    path::Path,
+    render::{DrawContext, StrokeStyle},
+    snapshot::{SnapshotConfig, render},
+};
+
+const POINTS: usize = 100_000;
+
+/// Limits large enough for the whole chart to fit in one draw call with 32-bit indices.
+fn large_batches(index_format: IndexFormat) -> SnapshotConfig {
+    SnapshotConfig {
+        max_vertices_per_draw: 2_000_000,
+        max_indices_per_draw: 6_000_000,
+        index_format,
+        ..Default::default()
+    }
+}
+
+fn curve(x: f32) -> f32 {
+    64. + 40. * (x / 8.).sin()
+}
+
+/// A line chart of `POINTS` samples across the frame.
+fn draw_chart(draw_context: &mut DrawContext) {
+    let points: Vec<Point> = (0..POINTS)
+        .map(|i| {
+            let x = 4. + i as f32 * 120. / POINTS as f32;
+            Point { x, y: curve(x) }
+        })
+        .collect();
+
+    draw_context.draw_polyline(
+        &points,
+        false,
+        StrokeStyle {
+            thickness: 2.,
+            ..Default::default()
+        },
+        Color::from_rgba8(255, 255, 255, 255),
+    );
+}
+
+/// A circle of `POINTS` line segments.
+fn dense_circle() -> Path {
+    let mut path = Path::new();
+    path.move_to(104., 64.);
+    for i in 1..POINTS {
+        let angle = i as f32 / POINTS as f32 * TAU;
+        path.line_to(64. + 40. * angle.cos(), 64. + 40. * angle.sin());
+    }
+    path.close();
+
+    path
+}
+
+fn draw_dense_circle(draw_context: &mut DrawContext) {
+    draw_context.draw_path_stroke(
+        &dense_circle(),
+        StrokeStyle {
+            thickness: 4.,
+            ..Default::default()
+        },
+        Color::from_rgba8(0, 255, 0, 255),
+    );
+}
+
+#[test]
+fn dense_chart_renders_along_its_curve() {
+    let image = render(&large_batches(IndexFormat::U32), scene(draw_chart));
+
+    for x in [8, 32, 64, 100, 120] {
+        let y = curve(x as f32 + 0.5).floor() as usize;
+        assert_eq!(
+            pixel(&image, x, y),
+            [255, 255, 255, 255],
+            "curve at x = {x}"
+        );
+    }
+    assert_eq!(pixel(&image, 64, 4), [0, 0, 0, 255]);
+    assert_eq!(pixel(&image, 64, 124), [0, 0, 0, 255]);
+}
+
+#[test]
+fn dense_chart_matches_across_index_formats() {
+    let split = render(&SnapshotConfig::default(), scene(draw_chart));
+    let u32_batch = render(&large_batches(IndexFormat::U32), scene(draw_chart));
+    let u16_batch = render(&large_batches(IndexFormat::U16), scene(draw_chart));
+
+    assert!(
+        split.pixels == u32_batch.pixels,
+        "one u32 draw call differs"
+    );
+    assert!(split.pixels == u16_batch.pixels, "u16 draw calls differ");
+}
+
+#[test]
+fn dense_path_strokes_without_wrapping() {
+    let image = render(&large_batches(IndexFormat::U32), scene(draw_dense_circle));
+
+    for (x, y) in [(104, 64), (24, 64), (64, 104), (64, 24)] {
+        assert_eq!(
+            pixel(&image, x, y),
+            [0, 255, 0, 255],
+            "outline at ({x}, {y})"
+        );
+    }
+    assert_eq!(pixel(&image, 64, 64), [0, 0, 0, 255]);
+    assert_eq!(pixel(&image, 10, 10), [0, 0, 0, 255]);
+}
+
+#[test]
+fn dense_path_matches_across_index_formats() {
+    let split = render(&SnapshotConfig::default(), scene(draw_dense_circle));
+    let u32_batch = render(&large_batches(IndexFormat::U32), scene(draw_dense_circle));
+    let u16_batch = render(&large_batches(IndexFormat::U16), scene(draw_dense_circle));
+
+    assert!(
+        split.pixels == u32_batch.pixels,
+        "one u32 draw call differs"
+    );
+    assert!(split.pixels == u16_batch.pixels, "u16 draw calls differ");
+}
@@ -0,0 +1,127 @@
+use std::{cell::RefCell, rc::Rc};
+
+use miniquad::EventHandler;
+use porcelain::{
+    backend::SoftwareBackend,
+    conf::WindowConfig,
+    input::{InputEvent, KeyCode, KeyMods, MouseButton, TouchPhase},
+    render::{DrawContext, EventListener, RendererContext},
+    texture::TextureContext,
+};
+
+struct Recorder {
+    events: Rc<RefCell<Vec<InputEvent>>>,
+}
+
+impl EventListener for Recorder {
+    fn update(&mut self, _texture_context: &TextureContext, _dt: f64) {}
+
+    fn draw(&self, _draw_context: &mut DrawContext) {}
+
+    fn input(&mut self, event: &InputEvent) {
+        self.events.borrow_mut().push(*event);
+    }
+}
+
+fn renderer(
+    dpi_scale: f32,
+) -> (
+    RendererContext<Recorder, SoftwareBackend>,
+    Rc<RefCell<Vec<InputEvent>>>,
+) {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let renderer = RendererContext::with_backend(
+        WindowConfig::default(),
+        Recorder {
+            events: events.clone(),
+        },
+        SoftwareBackend::new(200, 100).with_dpi_scale(dpi_scale),
+    );
+
+    (renderer, events)
+}
+
+#[test]
+fn forwards_keyboard_and_wheel_events() {
+    let (mut renderer, events) = renderer(1.);
+    let modifiers = KeyMods {
+        shift: true,
+        ..Default::default()
+    };
+
+    renderer.key_down_event(KeyCode::A, modifiers, false);
+    renderer.char_event('A', modifiers, false);
+    renderer.key_up_event(KeyCode::A, modifiers);
+    renderer.mouse_wheel_event(0., -3.);
+
+    assert_eq!(
+        *events.borrow(),
+        [
+            InputEvent::KeyDown {
+                keycode: KeyCode::A,
+                modifiers,
+                repeat: false
+            },
+            InputEvent::Char {
+                character: 'A',
+                modifiers,
+                repeat: false
+            },
+            InputEvent::KeyUp {
+                keycode: KeyCode::A,
+                modifiers
+            },
+            InputEvent::MouseWheel { dx: 0., dy: -3. },
+        ]
+    );
+}
+
+#[test]
+fn pointer_positions_are_in_logical_units() {
+    let (mut renderer, events) = renderer(2.);
+
+    renderer.mouse_motion_event(100., 50.);
+    renderer.mouse_button_down_event(MouseButton::Right, 40., 20.);
+    renderer.mouse_button_up_event(MouseButton::Right, 40., 20.);
+
+    assert_eq!(
+        *events.borrow(),
+        [
+            InputEvent::MouseMotion { x: 50., y: 25. },
+            InputEvent::MouseButtonDown {
+                button: MouseButton::Right,
+                x: 20.,
+                y: 10.
+            },
+            InputEvent::MouseButtonUp {
+                button: MouseButton::Right,
+                x: 20.,
+                y: 10.
+            },
+        ]
+    );
+}
+
+#[test]
+fn touches_also_emulate_the_mouse() {
+    let (mut renderer, events) = renderer(2.);
+
+    renderer.touch_event(TouchPhase::Started, 7, 10., 10.);
+
+    assert_eq!(
+        *events.borrow(),
+        [
+            InputEvent::Touch {
+                phase: TouchPhase::Started,
+                id: 7,
+                x: 5.,
+                y: 5.
+            },
+            InputEvent::MouseButtonDown {
+                button: MouseButton::Left,
+                x: 5.,
+                y: 5.
+            },
+        ]
+    );
+}
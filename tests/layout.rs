@@ -0,0 +1,292 @@
+mod common;
+
+use common::snapshot;
+use porcelain::{
+    Rect,
+    color::Color,
+    layout::{
+        AlignX, AlignY, ChildAlignment, ElementConfig, Layout, LayoutDirection, Padding, Sizing,
+        SizingAxis,
+    },
+};
+
+fn fixed(width: f32, height: f32) -> ElementConfig {
+    ElementConfig {
+        sizing: Sizing {
+            width: SizingAxis::Fixed(width),
+            height: SizingAxis::Fixed(height),
+        },
+        ..Default::default()
+    }
+}
+
+fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect {
+    Rect { x, y, w, h }
+}
+
+#[test]
+fn fit_wraps_children_with_padding_and_gap() {
+    let mut layout = Layout::new(800., 600.);
+    let mut children = Vec::new();
+    let parent = layout.element(
+        ElementConfig {
+            padding: Padding::all(10.),
+            child_gap: 5.,
+            ..Default::default()
+        },
+        |layout| {
+            children.push(layout.leaf(fixed(50., 20.)));
+            children.push(layout.leaf(fixed(30., 40.)));
+        },
+    );
+    let computed = layout.compute();
+
+    assert_eq!(computed.rect(parent), rect(0., 0., 105., 60.));
+    assert_eq!(computed.rect(children[0]), rect(10., 10., 50., 20.));
+    assert_eq!(computed.rect(children[1]), rect(65., 10., 30., 40.));
+}
+
+#[test]
+fn grow_shares_free_space_evenly() {
+    let mut layout = Layout::new(300., 100.);
+    let mut children = Vec::new();
+    layout.element(
+        ElementConfig {
+            sizing: Sizing {
+                width: SizingAxis::grow(),
+                height: SizingAxis::grow(),
+            },
+            child_gap: 10.,
+            ..Default::default()
+        },
+        |layout| {
+            children.push(layout.leaf(fixed(80., 10.)));
+            for _ in 0..2 {
+                children.push(layout.leaf(ElementConfig {
+                    sizing: Sizing {
+                        width: SizingAxis::grow(),
+                        height: SizingAxis::grow(),
+                    },
+                    ..Default::default()
+                }));
+            }
+        },
+    );
+    let computed = layout.compute();
+
+    assert_eq!(computed.rect(children[1]), rect(90., 0., 100., 100.));
+    assert_eq!(computed.rect(children[2]), rect(200., 0., 100., 100.));
+}
+
+#[test]
+fn grow_respects_max_and_catches_up_smaller_elements() {
+    let mut layout = Layout::new(300., 100.);
+    let mut children = Vec::new();
+    layout.element(
+        ElementConfig {
+            sizing: Sizing {
+                width: SizingAxis::Fixed(300.),
+                height: SizingAxis::fit(),
+            },
+            ..Default::default()
+        },
+        |layout| {
+            children.push(layout.leaf(ElementConfig {
+                sizing: Sizing {
+                    width: SizingAxis::Grow { min: 0., max: 50. },
+                    height: SizingAxis::fit(),
+                },
+                ..Default::default()
+            }));
+            children.push(layout.element(
+                ElementConfig {
+                    sizing: Sizing {
+                        width: SizingAxis::grow(),
+                        height: SizingAxis::fit(),
+                    },
+                    ..Default::default()
+                },
+                |layout| {
+                    layout.leaf(fixed(150., 10.));
+                },
+            ));
+            children.push(layout.leaf(ElementConfig {
+                sizing: Sizing {
+                    width: SizingAxis::grow(),
+                    height: SizingAxis::fit(),
+                },
+                ..Default::default()
+            }));
+        },
+    );
+    let computed = layout.compute();
+
+    assert_eq!(computed.rect(children[0]).w, 50.);
+    assert_eq!(computed.rect(children[1]).w, 150.);
+    assert_eq!(computed.rect(children[2]).w, 100.);
+}
+
+#[test]
+fn percent_uses_space_left_after_padding_and_gaps() {
+    let mut layout = Layout::new(220., 100.);
+    let mut children = Vec::new();
+    layout.element(
+        ElementConfig {
+            sizing: Sizing {
+                width: SizingAxis::grow(),
+                height: SizingAxis::grow(),
+            },
+            padding: Padding::all(10.),
+            child_gap: 20.,
+            ..Default::default()
+        },
+        |layout| {
+            for percent in [0.25, 0.75] {
+                children.push(layout.leaf(ElementConfig {
+                    sizing: Sizing {
+                        width: SizingAxis::Percent(percent),
+                        height: SizingAxis::Percent(0.5),
+                    },
+                    ..Default::default()
+                }));
+            }
+        },
+    );
+    let computed = layout.compute();
+
+    assert_eq!(computed.rect(children[0]), rect(10., 10., 45., 40.));
+    assert_eq!(computed.rect(children[1]), rect(75., 10., 135., 40.));
+}
+
+#[test]
+fn overflow_shrinks_largest_elements_down_to_min() {
+    let mut layout = Layout::new(100., 100.);
+    let mut children = Vec::new();
+    for (min, width) in [(0., 80.), (60., 80.)] {
+        children.push(layout.element(
+            ElementConfig {
+                sizing: Sizing {
+                    width: SizingAxis::Fit {
+                        min,
+                        max: f32::INFINITY,
+                    },
+                    height: SizingAxis::fit(),
+                },
+                ..Default::default()
+            },
+            |layout| {
+                layout.leaf(fixed(width, 10.));
+            },
+        ));
+    }
+    let computed = layout.compute();
+
+    assert_eq!(computed.rect(children[0]).w, 40.);
+    assert_eq!(computed.rect(children[1]).w, 60.);
+}
+
+#[test]
+fn alignment_on_both_axes() {
+    let mut layout = Layout::with_root(ElementConfig {
+        sizing: Sizing {
+            width: SizingAxis::Fixed(200.),
+            height: SizingAxis::Fixed(100.),
+        },
+        direction: LayoutDirection::TopToBottom,
+        child_gap: 10.,
+        child_alignment: ChildAlignment {
+            x: AlignX::Right,
+            y: AlignY::Center,
+        },
+        ..Default::default()
+    });
+    let first = layout.leaf(fixed(50., 20.));
+    let second = layout.leaf(fixed(100., 30.));
+    let computed = layout.compute();
+
+    assert_eq!(computed.rect(first), rect(150., 20., 50., 20.));
+    assert_eq!(computed.rect(second), rect(100., 50., 100., 30.));
+}
+
+#[test]
+fn screen_snapshot() {
+    snapshot("layout", |draw_context| {
+        let (width, height) = draw_context.screen_size();
+        let grow = Sizing {
+            width: SizingAxis::grow(),
+            height: SizingAxis::grow(),
+        };
+
+        let mut layout = Layout::with_root(ElementConfig {
+            sizing: Sizing {
+                width: SizingAxis::Fixed(width),
+                height: SizingAxis::Fixed(height),
+            },
+            padding: Padding::all(8.),
+            child_gap: 8.,
+            direction: LayoutDirection::TopToBottom,
+            background: Some(Color::from_rgba8(32, 32, 40, 255)),
+            ..Default::default()
+        });
+        layout.leaf(ElementConfig {
+            sizing: Sizing {
+                width: SizingAxis::grow(),
+                height: SizingAxis::Fixed(16.),
+            },
+            background: Some(Color::from_rgba8(200, 80, 80, 255)),
+            ..Default::default()
+        });
+        layout.element(
+            ElementConfig {
+                sizing: grow,
+                child_gap: 8.,
+                ..Default::default()
+            },
+            |layout| {
+                layout.element(
+                    ElementConfig {
+                        sizing: Sizing {
+                            width: SizingAxis::Percent(0.3),
+                            height: SizingAxis::grow(),
+                        },
+                        padding: Padding::all(4.),
+                        child_gap: 4.,
+                        direction: LayoutDirection::TopToBottom,
+                        background: Some(Color::from_rgba8(80, 80, 200, 255)),
+                        ..Default::default()
+                    },
+                    |layout| {
+                        for _ in 0..3 {
+                            layout.leaf(ElementConfig {
+                                sizing: Sizing {
+                                    width: SizingAxis::grow(),
+                                    height: SizingAxis::Fixed(10.),
+                                },
+                                background: Some(Color::from_rgba8(220, 220, 220, 255)),
+                                ..Default::default()
+                            });
+                        }
+                    },
+                );
+                layout.element(
+                    ElementConfig {
+                        sizing: grow,
+                        child_alignment: ChildAlignment {
+                            x: AlignX::Center,
+                            y: AlignY::Center,
+                        },
+                        background: Some(Color::from_rgba8(80, 200, 80, 255)),
+                        ..Default::default()
+                    },
+                    |layout| {
+                        layout.leaf(ElementConfig {
+                            background: Some(Color::from_rgba8(240, 240, 80, 255)),
+                            ..fixed(24., 24.)
+                        });
+                    },
+                );
+            },
+        );
+        layout.end(draw_context);
+    });
+}
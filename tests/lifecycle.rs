@@ -0,0 +1,220 @@
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use miniquad::EventHandler;
+use porcelain::{
+    backend::SoftwareBackend,
+    color::Color,
+    conf::WindowConfig,
+    input::DroppedFile,
+    render::{DrawContext, EventListener, RendererContext, TextureArea},
+    snapshot::{SnapshotConfig, render},
+    texture::{Texture, TextureContext, TextureFormat},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Hook {
+    Init,
+    Update,
+    Resize(f32, f32),
+    FocusGained,
+    FocusLost,
+    Minimized,
+    Restored,
+    QuitRequested,
+    FilesDropped(Vec<DroppedFile>),
+}
+
+struct Recorder {
+    hooks: Rc<RefCell<Vec<Hook>>>,
+    allow_quit: bool,
+}
+
+impl EventListener for Recorder {
+    fn update(&mut self, _texture_context: &TextureContext, _dt: f64) {
+        self.hooks.borrow_mut().push(Hook::Update);
+    }
+
+    fn draw(&self, _draw_context: &mut DrawContext) {}
+
+    fn init(&mut self, _texture_context: &TextureContext) {
+        self.hooks.borrow_mut().push(Hook::Init);
+    }
+
+    fn resize(&mut self, width: f32, height: f32) {
+        self.hooks.borrow_mut().push(Hook::Resize(width, height));
+    }
+
+    fn focus_gained(&mut self) {
+        self.hooks.borrow_mut().push(Hook::FocusGained);
+    }
+
+    fn focus_lost(&mut self) {
+        self.hooks.borrow_mut().push(Hook::FocusLost);
+    }
+
+    fn minimized(&mut self) {
+        self.hooks.borrow_mut().push(Hook::Minimized);
+    }
+
+    fn restored(&mut self) {
+        self.hooks.borrow_mut().push(Hook::Restored);
+    }
+
+    fn quit_requested(&mut self) -> bool {
+        self.hooks.borrow_mut().push(Hook::QuitRequested);
+        self.allow_quit
+    }
+
+    fn files_dropped(&mut self, files: &[DroppedFile]) {
+        self.hooks
+            .borrow_mut()
+            .push(Hook::FilesDropped(files.to_vec()));
+    }
+}
+
+fn renderer(
+    allow_quit: bool,
+    backend: SoftwareBackend,
+) -> (
+    RendererContext<Recorder, SoftwareBackend>,
+    Rc<RefCell<Vec<Hook>>>,
+) {
+    let hooks = Rc::new(RefCell::new(Vec::new()));
+    let renderer = RendererContext::with_backend(
+        WindowConfig::default(),
+        Recorder {
+            hooks: hooks.clone(),
+            allow_quit,
+        },
+        backend,
+    );
+
+    (renderer, hooks)
+}
+
+/// Registers its texture in `init` and draws it, without any `update`.
+struct InitTexture {
+    texture: Option<Texture>,
+}
+
+impl EventListener for InitTexture {
+    fn update(&mut self, _texture_context: &TextureContext, _dt: f64) {}
+
+    fn draw(&self, draw_context: &mut DrawContext) {
+        let texture = self.texture.as_ref().unwrap();
+        draw_context.draw_texture(
+            0.,
+            0.,
+            16.,
+            16.,
+            TextureArea {
+                texture: texture.id(),
+                location: glam::Vec2::ZERO,
+                size: glam::Vec2::ONE,
+            },
+            Color::from_rgba8(255, 255, 255, 255),
+        );
+    }
+
+    fn init(&mut self, texture_context: &TextureContext) {
+        self.texture = Some(texture_context.create_texture(
+            1,
+            1,
+            TextureFormat::RGBA8,
+            &[0, 0, 255, 255],
+            Default::default(),
+        ));
+    }
+}
+
+#[test]
+fn init_runs_once_before_the_first_update() {
+    let (mut renderer, hooks) = renderer(true, SoftwareBackend::new(16, 16));
+    assert_eq!(*hooks.borrow(), [Hook::Init]);
+
+    renderer.update();
+    renderer.update();
+
+    assert_eq!(*hooks.borrow(), [Hook::Init, Hook::Update, Hook::Update]);
+}
+
+#[test]
+fn init_can_register_textures() {
+    let image = render(&SnapshotConfig::default(), InitTexture { texture: None });
+
+    assert_eq!(image.pixels[..4], [0, 0, 255, 255]);
+}
+
+#[test]
+fn resize_reports_logical_size() {
+    let (mut renderer, hooks) = renderer(true, SoftwareBackend::new(16, 16).with_dpi_scale(2.));
+
+    renderer.resize_event(640., 480.);
+
+    assert_eq!(hooks.borrow()[1..], [Hook::Resize(320., 240.)]);
+    assert_eq!(renderer.backend().width(), 640);
+}
+
+#[test]
+fn focus_follows_minimize_and_restore() {
+    let (mut renderer, hooks) = renderer(true, SoftwareBackend::new(16, 16));
+
+    renderer.window_minimized_event();
+    renderer.window_minimized_event();
+    renderer.window_restored_event();
+    renderer.window_restored_event();
+
+    assert_eq!(
+        hooks.borrow()[1..],
+        [
+            Hook::Minimized,
+            Hook::FocusLost,
+            Hook::Minimized,
+            Hook::Restored,
+            Hook::FocusGained,
+            Hook::Restored,
+        ]
+    );
+}
+
+#[test]
+fn quit_is_cancelled_when_the_listener_refuses() {
+    let (mut renderer, hooks) = renderer(false, SoftwareBackend::new(16, 16));
+
+    renderer.quit_requested_event();
+
+    assert_eq!(hooks.borrow()[1..], [Hook::QuitRequested]);
+    assert!(renderer.backend().quit_cancelled());
+}
+
+#[test]
+fn quit_goes_ahead_when_the_listener_allows() {
+    let (mut renderer, hooks) = renderer(true, SoftwareBackend::new(16, 16));
+
+    renderer.quit_requested_event();
+
+    assert_eq!(hooks.borrow()[1..], [Hook::QuitRequested]);
+    assert!(!renderer.backend().quit_cancelled());
+}
+
+#[test]
+fn dropped_files_are_forwarded() {
+    let files = vec![
+        DroppedFile {
+            path: Some(PathBuf::from("/tmp/level.json")),
+            bytes: Some(b"{}".to_vec()),
+        },
+        DroppedFile {
+            path: None,
+            bytes: Some(vec![1, 2, 3]),
+        },
+    ];
+    let (mut renderer, hooks) = renderer(
+        true,
+        SoftwareBackend::new(16, 16).with_dropped_files(files.clone()),
+    );
+
+    renderer.files_dropped_event();
+
+    assert_eq!(hooks.borrow()[1..], [Hook::FilesDropped(files)]);
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::{pixel, scene_with};
+use glam::Vec4;
+use porcelain::{
+    Error,
+    color::Color,
+    material::{Material, MaterialParams, UniformValue},
+    render::DrawContext,
+    snapshot::{SnapshotConfig, render},
+    texture::TextureContext,
+};
+
+const VERTEX: &str = r"
+#version 100
+attribute vec2 in_pos;
+attribute vec4 in_color;
+attribute vec2 in_texcoord;
+
+uniform mat4 model;
+uniform mat4 projection;
+
+void main() {
+    gl_Position = projection * model * vec4(in_pos, 0, 1);
+}";
+
+const FRAGMENT: &str = r"
+#version 100
+uniform lowp vec4 tint;
+
+void main() {
+    gl_FragColor = tint;
+}";
+
+fn params() -> MaterialParams<'static> {
+    MaterialParams {
+        vertex: VERTEX,
+        fragment: FRAGMENT,
+        metal: "",
+        uniforms: &[("tint", UniformValue::Vec4(Vec4::ONE))],
+    }
+}
+
+/// The pixel at (`x`, `y`) of a frame drawn with a material created on update.
+fn material_pixel(
+    draw: impl Fn(&mut DrawContext, &Material) + 'static,
+    x: usize,
+    y: usize,
+) -> [u8; 4] {
+    let scene = scene_with(
+        |texture_context: &TextureContext| texture_context.create_material(&params()).unwrap(),
+        draw,
+    );
+
+    pixel(&render(&SnapshotConfig::default(), scene), x, y)
+}
+
+fn red() -> Color {
+    Color::from_rgba8(255, 0, 0, 255)
+}
+
+#[test]
+fn uniforms_start_with_their_initial_values() {
+    let draw = |_: &mut DrawContext, material: &Material| {
+        assert_eq!(
+            material.uniform("tint"),
+            Some(UniformValue::Vec4(Vec4::ONE))
+        );
+        assert_eq!(material.uniform("missing"), None);
+
+        let mut material = material.clone();
+        material.set_uniform("tint", UniformValue::Vec4(Vec4::ZERO));
+        assert_eq!(
+            material.uniform("tint"),
+            Some(UniformValue::Vec4(Vec4::ZERO))
+        );
+    };
+
+    material_pixel(draw, 0, 0);
+}
+
+#[test]
+#[should_panic(expected = "Uniform tint cannot change its type")]
+fn uniforms_keep_their_type() {
+    material_pixel(
+        |_, material| {
+            material
+                .clone()
+                .set_uniform("tint", UniformValue::Float(1.))
+        },
+        0,
+        0,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Material has no uniform named missing")]
+fn unknown_uniforms_are_rejected() {
+    material_pixel(
+        |_, material| {
+            material
+                .clone()
+                .set_uniform("missing", UniformValue::Float(1.))
+        },
+        0,
+        0,
+    );
+}
+
+#[test]
+fn invalid_uniform_updates_are_errors() {
+    let draw = |_: &mut DrawContext, material: &Material| {
+        let mut material = material.clone();
+
+        assert!(matches!(
+            material.try_set_uniform("missing", UniformValue::Float(1.)),
+            Err(Error::UnknownUniform(name)) if name == "missing"
+        ));
+        assert!(matches!(
+            material.try_set_uniform("tint", UniformValue::Float(1.)),
+            Err(Error::UniformTypeMismatch(name)) if name == "tint"
+        ));
+        assert_eq!(
+            material.uniform("tint"),
+            Some(UniformValue::Vec4(Vec4::ONE))
+        );
+        assert!(
+            material
+                .try_set_uniform("tint", UniformValue::Vec4(Vec4::ZERO))
+                .is_ok()
+        );
+    };
+
+    material_pixel(draw, 0, 0);
+}
+
+#[test]
+fn software_backend_falls_back_to_built_in_shading() {
+    let draw = |draw_context: &mut DrawContext, material: &Material| {
+        draw_context.push_material(material);
+        draw_context.draw_rect(0., 0., 32., 32., red());
+        draw_context.pop_material();
+
+        draw_context.draw_rect(32., 0., 32., 32., Color::from_rgba8(0, 0, 255, 255));
+    };
+
+    assert_eq!(material_pixel(draw, 16, 16), [255, 0, 0, 255]);
+    assert_eq!(material_pixel(draw, 48, 16), [0, 0, 255, 255]);
+}
+
+#[test]
+#[should_panic(expected = "pop_material called without a matching push_material")]
+fn unbalanced_pop_material_panics() {
+    material_pixel(|draw_context, _| draw_context.pop_material(), 0, 0);
+}
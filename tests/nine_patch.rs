@@ -0,0 +1,234 @@
+mod common;
+
+use common::{golden, pixel, scene_with};
+use porcelain::{
+    Rect,
+    color::Color,
+    render::{BorderWidths, DrawContext, EventListener, NinePatch, NinePatchMode, TextureArea},
+    snapshot::{Image, SnapshotConfig, assert_snapshot},
+    texture::{FilterMode, TextureContext, TextureFormat, TextureOptions, TextureWrap},
+};
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+/// A 4x3 image with one pixel borders: red corners, a green and blue middle column
+/// and a white center line on the right.
+///
+/// ```text
+/// R G B R
+/// G B W G
+/// R G B R
+/// ```
+fn frame_pixels() -> Vec<u8> {
+    [
+        RED, GREEN, BLUE, RED, //
+        GREEN, BLUE, WHITE, GREEN, //
+        RED, GREEN, BLUE, RED,
+    ]
+    .concat()
+}
+
+/// Uploads the frame image on update and passes it to the draw closure.
+fn frame_scene(
+    draw: impl Fn(&mut DrawContext, TextureArea) + 'static,
+) -> impl EventListener + 'static {
+    scene_with(
+        |texture_context: &TextureContext| {
+            texture_context
+                .create_texture(
+                    4,
+                    3,
+                    TextureFormat::RGBA8,
+                    &frame_pixels(),
+                    TextureOptions {
+                        filter: FilterMode::Nearest,
+                        wrap: TextureWrap::Clamp,
+                        mipmaps: false,
+                    },
+                )
+                .texture_area()
+        },
+        move |draw_context: &mut DrawContext, texture: &TextureArea| draw(draw_context, *texture),
+    )
+}
+
+fn render(draw: impl Fn(&mut DrawContext, TextureArea) + 'static) -> Image {
+    porcelain::snapshot::render(&SnapshotConfig::default(), frame_scene(draw))
+}
+
+fn white() -> Color {
+    Color::from_rgba8(255, 255, 255, 255)
+}
+
+/// The frame image with its borders drawn 8 units wide.
+fn patch(mode: NinePatchMode) -> NinePatch {
+    NinePatch {
+        scale: 8.,
+        mode,
+        ..NinePatch::new(4., 3., BorderWidths::all(1.))
+    }
+}
+
+fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect {
+    Rect { x, y, w, h }
+}
+
+#[test]
+fn corners_keep_their_size() {
+    let image = render(|draw_context, texture| {
+        draw_context.draw_nine_patch(
+            rect(8., 8., 80., 48.),
+            texture,
+            patch(NinePatchMode::Stretch),
+            white(),
+        );
+    });
+
+    for (x, y) in [(8, 8), (15, 15), (87, 8), (80, 15), (8, 55), (87, 55)] {
+        assert_eq!(pixel(&image, x, y), RED, "corner pixel at ({x}, {y})");
+    }
+    assert_eq!(pixel(&image, 16, 8), GREEN);
+    assert_eq!(pixel(&image, 8, 16), GREEN);
+    assert_eq!(pixel(&image, 79, 55), BLUE);
+}
+
+#[test]
+fn stretch_scales_edges_and_center() {
+    let image = render(|draw_context, texture| {
+        draw_context.draw_nine_patch(
+            rect(8., 8., 80., 48.),
+            texture,
+            patch(NinePatchMode::Stretch),
+            white(),
+        );
+    });
+
+    // The 64 units between the corners show the 2 middle pixels once, 32 units each
+    assert_eq!(pixel(&image, 47, 10), GREEN);
+    assert_eq!(pixel(&image, 48, 10), BLUE);
+    assert_eq!(pixel(&image, 47, 32), BLUE);
+    assert_eq!(pixel(&image, 48, 32), WHITE);
+}
+
+#[test]
+fn tile_repeats_edges_and_center() {
+    let image = render(|draw_context, texture| {
+        draw_context.draw_nine_patch(
+            rect(8., 8., 80., 48.),
+            texture,
+            patch(NinePatchMode::Tile),
+            white(),
+        );
+    });
+
+    // Tiles are the 2 middle pixels drawn 16 units wide, repeated from the left corner
+    for tile in 0..4 {
+        let x = 16 + tile * 16;
+        assert_eq!(pixel(&image, x, 10), GREEN, "tile {tile}");
+        assert_eq!(pixel(&image, x + 8, 10), BLUE, "tile {tile}");
+        assert_eq!(pixel(&image, x, 32), BLUE, "tile {tile}");
+        assert_eq!(pixel(&image, x + 8, 32), WHITE, "tile {tile}");
+    }
+}
+
+#[test]
+fn last_tile_is_cut_off() {
+    let image = render(|draw_context, texture| {
+        draw_context.draw_nine_patch(
+            rect(0., 0., 40., 24.),
+            texture,
+            patch(NinePatchMode::Tile),
+            white(),
+        );
+    });
+
+    // 24 units between the corners: one full tile, then only the green half of the next
+    assert_eq!(pixel(&image, 8, 2), GREEN);
+    assert_eq!(pixel(&image, 16, 2), BLUE);
+    assert_eq!(pixel(&image, 24, 2), GREEN);
+    assert_eq!(pixel(&image, 31, 2), GREEN);
+    assert_eq!(pixel(&image, 32, 2), RED);
+}
+
+#[test]
+fn borders_shrink_in_small_rects() {
+    let image = render(|draw_context, texture| {
+        draw_context.draw_nine_patch(
+            rect(0., 0., 8., 8.),
+            texture,
+            patch(NinePatchMode::Stretch),
+            white(),
+        );
+    });
+
+    // Only the four corners fit, at half their size
+    assert_eq!(pixel(&image, 3, 3), RED);
+    assert_eq!(pixel(&image, 4, 4), RED);
+    assert_eq!(pixel(&image, 8, 8), [0, 0, 0, 255]);
+}
+
+#[test]
+fn tint_multiplies_the_image() {
+    let image = render(|draw_context, texture| {
+        draw_context.draw_nine_patch(
+            rect(0., 0., 32., 32.),
+            texture,
+            patch(NinePatchMode::Stretch),
+            Color::from_rgba8(0, 255, 255, 255),
+        );
+    });
+
+    assert_eq!(pixel(&image, 2, 2), [0, 0, 0, 255]);
+    assert_eq!(pixel(&image, 12, 2), GREEN);
+}
+
+#[test]
+fn atlas_images_keep_to_their_area() {
+    let image = porcelain::snapshot::render(
+        &SnapshotConfig::default(),
+        scene_with(
+            |texture_context: &TextureContext| {
+                // Another image first, so the frame is not at the origin of the page
+                texture_context.register_atlas_image_rgba8(2, 2, &[WHITE; 4].concat());
+                texture_context.register_atlas_image_rgba8(4, 3, &frame_pixels())
+            },
+            |draw_context: &mut DrawContext, area: &TextureArea| {
+                draw_context.draw_nine_patch(
+                    rect(0., 0., 64., 48.),
+                    *area,
+                    patch(NinePatchMode::Tile),
+                    white(),
+                );
+            },
+        ),
+    );
+
+    // Atlas pages are filtered linearly, these only blend with the extruded edge pixels
+    assert_eq!(pixel(&image, 3, 3), RED);
+    assert_eq!(pixel(&image, 60, 44), RED);
+}
+
+#[test]
+fn nine_patch_modes() {
+    assert_snapshot(
+        golden("nine_patch"),
+        &SnapshotConfig::default(),
+        frame_scene(|draw_context, texture| {
+            draw_context.draw_nine_patch(
+                rect(4., 4., 120., 56.),
+                texture,
+                patch(NinePatchMode::Stretch),
+                white(),
+            );
+            draw_context.draw_nine_patch(
+                rect(4., 68., 120., 56.),
+                texture,
+                patch(NinePatchMode::Tile),
+                white(),
+            );
+        }),
+    );
+}
@@ -0,0 +1,256 @@
+mod common;
+
+use std::f32::consts::TAU;
+
+use common::{draw_pixel, pixel, scene, snapshot};
+use glam::{Affine2, Vec2};
+use miniquad::EventHandler;
+use porcelain::{
+    backend::SoftwareBackend,
+    color::Color,
+    conf::{IndexFormat, WindowConfig},
+    path::{FillRule, Path},
+    render::{DrawContext, LineCap, LineJoin, RendererContext, StrokeStyle},
+    snapshot::Image,
+};
+
+fn red() -> Color {
+    Color::from_rgba8(255, 0, 0, 255)
+}
+
+/// Five-pointed star drawn in one stroke, so its center is wound around twice.
+fn star(x: f32, y: f32, radius: f32) -> Path {
+    let mut path = Path::new();
+    for i in 0..5 {
+        let angle = (i as f32 * 144. - 90.).to_radians();
+        path.line_to(x + radius * angle.cos(), y + radius * angle.sin());
+    }
+    path.close();
+    path
+}
+
+/// Square with a square hole, the hole going the other way around when `reversed`.
+fn frame(reversed: bool) -> Path {
+    let mut path = Path::new();
+    path.move_to(16., 16.)
+        .line_to(112., 16.)
+        .line_to(112., 112.)
+        .line_to(16., 112.)
+        .close();
+    if reversed {
+        path.move_to(48., 48.)
+            .line_to(48., 80.)
+            .line_to(80., 80.)
+            .line_to(80., 48.)
+            .close();
+    } else {
+        path.move_to(48., 48.)
+            .line_to(80., 48.)
+            .line_to(80., 80.)
+            .line_to(48., 80.)
+            .close();
+    }
+    path
+}
+
+#[test]
+fn fills_and_strokes() {
+    snapshot("path", |draw_context| {
+        draw_context.draw_path(
+            &star(32., 34., 28.),
+            FillRule::NonZero,
+            Color::from_rgba8(240, 200, 80, 255),
+        );
+        draw_context.draw_path(
+            &star(96., 34., 28.),
+            FillRule::EvenOdd,
+            Color::from_rgba8(240, 200, 80, 255),
+        );
+
+        let mut curves = Path::new();
+        curves
+            .move_to(8., 120.)
+            .cubic_to(30., 60., 50., 130., 64., 90.)
+            .quad_to(80., 60., 120., 110.);
+        draw_context.draw_path_stroke(
+            &curves,
+            StrokeStyle {
+                thickness: 3.,
+                join: LineJoin::Round,
+                cap: LineCap::Round,
+                ..Default::default()
+            },
+            Color::from_rgba8(80, 200, 240, 255),
+        );
+
+        let mut rounded = Path::new();
+        rounded
+            .move_to(84., 72.)
+            .arc_to(120., 72., 120., 100., 12.)
+            .arc_to(120., 100., 84., 100., 4.)
+            .line_to(84., 100.)
+            .close();
+        draw_context.draw_path(
+            &rounded,
+            FillRule::NonZero,
+            Color::from_rgba8(200, 80, 240, 255),
+        );
+    });
+}
+
+#[test]
+fn fill_rules_differ_on_self_intersections() {
+    let fill = |fill_rule| {
+        move |draw_context: &mut DrawContext| {
+            draw_context.draw_path(&star(64., 64., 48.), fill_rule, red())
+        }
+    };
+
+    assert_eq!(
+        draw_pixel(fill(FillRule::NonZero), 64, 64),
+        [255, 0, 0, 255]
+    );
+    assert_eq!(draw_pixel(fill(FillRule::EvenOdd), 64, 64), [0, 0, 0, 255]);
+    // A tip is wound around once either way
+    assert_eq!(
+        draw_pixel(fill(FillRule::EvenOdd), 64, 22),
+        [255, 0, 0, 255]
+    );
+}
+
+#[test]
+fn holes_follow_the_fill_rule() {
+    let fill = |reversed, fill_rule| {
+        move |draw_context: &mut DrawContext| {
+            draw_context.draw_path(&frame(reversed), fill_rule, red())
+        }
+    };
+
+    for reversed in [false, true] {
+        assert_eq!(
+            draw_pixel(fill(reversed, FillRule::EvenOdd), 64, 64),
+            [0, 0, 0, 255]
+        );
+        assert_eq!(
+            draw_pixel(fill(reversed, FillRule::EvenOdd), 32, 64),
+            [255, 0, 0, 255]
+        );
+    }
+    assert_eq!(
+        draw_pixel(fill(false, FillRule::NonZero), 64, 64),
+        [255, 0, 0, 255]
+    );
+    assert_eq!(
+        draw_pixel(fill(true, FillRule::NonZero), 64, 64),
+        [0, 0, 0, 255]
+    );
+}
+
+#[test]
+fn arc_to_rounds_corners() {
+    let draw = |draw_context: &mut DrawContext| {
+        let mut path = Path::new();
+        path.move_to(16., 16.)
+            .arc_to(112., 16., 112., 112., 32.)
+            .line_to(112., 112.)
+            .line_to(16., 112.)
+            .close();
+        draw_context.draw_path(&path, FillRule::NonZero, red());
+    };
+
+    assert_eq!(draw_pixel(draw, 108, 20), [0, 0, 0, 255]);
+    assert_eq!(draw_pixel(draw, 96, 32), [255, 0, 0, 255]);
+    assert_eq!(draw_pixel(draw, 20, 20), [255, 0, 0, 255]);
+}
+
+#[test]
+fn curves_are_flattened_for_the_current_scale() {
+    // A circle of radius 10 scaled up 7 times
+    let draw = |draw_context: &mut DrawContext| {
+        let mut path = Path::new();
+        path.move_to(10., 0.)
+            .arc_to(10., 10., 0., 10., 10.)
+            .arc_to(-10., 10., -10., 0., 10.)
+            .arc_to(-10., -10., 0., -10., 10.)
+            .arc_to(10., -10., 10., 0., 10.)
+            .close();
+
+        draw_context.push_transform(Affine2::from_scale_angle_translation(
+            Vec2::splat(7.),
+            0.,
+            Vec2::new(64., 64.),
+        ));
+        draw_context.draw_path(&path, FillRule::NonZero, red());
+        draw_context.pop_transform();
+    };
+
+    // Pixel centers 68.6 and 71.4 away from the center, along the diagonal
+    assert_eq!(draw_pixel(draw, 112, 112), [255, 0, 0, 255]);
+    assert_eq!(draw_pixel(draw, 114, 114), [0, 0, 0, 255]);
+}
+
+/// A circle of 100 000 segments, more vertices than 16-bit indices can address.
+fn dense_circle() -> Path {
+    const POINTS: usize = 100_000;
+
+    let mut path = Path::new();
+    path.move_to(104., 64.);
+    for i in 1..POINTS {
+        let angle = i as f32 / POINTS as f32 * TAU;
+        path.line_to(64. + 40. * angle.cos(), 64. + 40. * angle.sin());
+    }
+    path.close();
+
+    path
+}
+
+/// Fills the dense circle, returning the frame and the draw calls it took.
+fn fill_dense_circle(index_format: IndexFormat) -> (Image, usize) {
+    let window_config = WindowConfig {
+        max_vertices_per_draw: 2_000_000,
+        max_indices_per_draw: 6_000_000,
+        index_format,
+        ..Default::default()
+    };
+    let mut renderer = RendererContext::with_backend(
+        window_config,
+        scene(|draw_context| {
+            draw_context.draw_path(
+                &dense_circle(),
+                FillRule::NonZero,
+                Color::from_rgba8(0, 0, 255, 255),
+            );
+        }),
+        SoftwareBackend::new(128, 128),
+    );
+    renderer.update();
+    renderer.draw();
+
+    let backend = renderer.backend();
+    let image = Image {
+        width: backend.width(),
+        height: backend.height(),
+        pixels: backend.pixels().to_vec(),
+    };
+    (image, backend.draw_call_count())
+}
+
+#[test]
+fn dense_paths_fill_in_one_draw_call_with_32_bit_indices() {
+    let (image, draw_calls) = fill_dense_circle(IndexFormat::U32);
+
+    assert_eq!(draw_calls, 1);
+    for (x, y) in [(64, 64), (30, 64), (98, 64), (64, 30), (64, 98)] {
+        assert_eq!(
+            pixel(&image, x, y),
+            [0, 0, 255, 255],
+            "inside at ({x}, {y})"
+        );
+    }
+    assert_eq!(pixel(&image, 10, 10), [0, 0, 0, 255]);
+    assert_eq!(pixel(&image, 64, 120), [0, 0, 0, 255]);
+
+    let (split, draw_calls) = fill_dense_circle(IndexFormat::U16);
+    assert!(draw_calls > 1);
+    assert!(split.pixels == image.pixels, "u16 draw calls differ");
+}
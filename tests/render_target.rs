@@ -0,0 +1,152 @@
+mod common;
+
+use common::{golden, pixel, scene_with};
+use glam::{Affine2, Vec2};
+use porcelain::{
+    Point,
+    color::Color,
+    render::{DrawContext, EventListener},
+    snapshot::{SnapshotConfig, assert_snapshot, render},
+    texture::{RenderTarget, TextureContext},
+};
+
+/// Creates a 32x32 render target on update and passes it to `draw`.
+fn target_scene(
+    draw: impl Fn(&mut DrawContext, RenderTarget) + 'static,
+) -> impl EventListener + 'static {
+    scene_with(
+        |texture_context: &TextureContext| texture_context.create_render_target(32, 32),
+        move |draw_context: &mut DrawContext, target: &RenderTarget| draw(draw_context, *target),
+    )
+}
+
+fn snapshot(name: &str, draw: impl Fn(&mut DrawContext, RenderTarget) + 'static) {
+    assert_snapshot(golden(name), &SnapshotConfig::default(), target_scene(draw));
+}
+
+fn target_pixel(
+    draw: impl Fn(&mut DrawContext, RenderTarget) + 'static,
+    x: usize,
+    y: usize,
+) -> [u8; 4] {
+    pixel(
+        &render(&SnapshotConfig::default(), target_scene(draw)),
+        x,
+        y,
+    )
+}
+
+fn red() -> Color {
+    Color::from_rgba8(255, 0, 0, 255)
+}
+
+fn white() -> Color {
+    Color::from_rgba8(255, 255, 255, 255)
+}
+
+/// A red square in the top-left quarter of the target over a white background.
+fn draw_quarter(draw_context: &mut DrawContext, target: RenderTarget) {
+    draw_context.push_render_target(target, Some(white()));
+    draw_context.draw_rect(0., 0., 16., 16., red());
+    draw_context.pop_render_target();
+}
+
+#[test]
+fn target_drawn_as_texture() {
+    snapshot("render_target", |draw_context, target| {
+        draw_context.push_render_target(target, Some(Color::from_rgba8(40, 40, 60, 255)));
+        draw_context.draw_circle(
+            &Point { x: 16., y: 16. },
+            12.,
+            Color::from_rgba8(240, 180, 40, 255),
+        );
+        draw_context.draw_rect(0., 0., 8., 8., red());
+        draw_context.pop_render_target();
+
+        draw_context.draw_texture(8., 8., 32., 32., target.texture_area(), white());
+        draw_context.draw_texture(48., 8., 64., 64., target.texture_area(), white());
+        draw_context.draw_texture(
+            8.,
+            80.,
+            32.,
+            32.,
+            target.texture_area(),
+            Color::from_rgba8(255, 255, 255, 128),
+        );
+    });
+}
+
+#[test]
+fn target_keeps_its_orientation() {
+    let draw = |draw_context: &mut DrawContext, target| {
+        draw_quarter(draw_context, target);
+        draw_context.draw_texture(0., 0., 32., 32., target.texture_area(), white());
+    };
+
+    assert_eq!(target_pixel(draw, 4, 4), [255, 0, 0, 255]);
+    assert_eq!(target_pixel(draw, 28, 4), [255, 255, 255, 255]);
+    assert_eq!(target_pixel(draw, 4, 28), [255, 255, 255, 255]);
+}
+
+#[test]
+fn target_starts_transparent_and_accumulates_without_clear() {
+    let draw = |draw_context: &mut DrawContext, target| {
+        draw_context.push_render_target(target, None);
+        draw_context.draw_rect(0., 0., 16., 32., red());
+        draw_context.pop_render_target();
+
+        draw_context.push_render_target(target, None);
+        draw_context.draw_rect(16., 0., 16., 32., Color::from_rgba8(0, 0, 255, 255));
+        draw_context.pop_render_target();
+
+        draw_context.draw_rect(0., 0., 64., 64., Color::from_rgba8(0, 255, 0, 255));
+        draw_context.draw_texture(0., 0., 64., 32., target.texture_area(), white());
+    };
+
+    assert_eq!(target_pixel(draw, 8, 8), [255, 0, 0, 255]);
+    assert_eq!(target_pixel(draw, 56, 8), [0, 0, 255, 255]);
+    // Below the texture, and nothing was drawn into the bottom of the target either
+    assert_eq!(target_pixel(draw, 8, 48), [0, 255, 0, 255]);
+}
+
+#[test]
+fn clip_and_transform_are_restored_after_pop() {
+    let draw = |draw_context: &mut DrawContext, target| {
+        draw_context.push_clip_rect(0., 0., 64., 64.);
+        draw_context.push_transform(Affine2::from_translation(Vec2::new(64., 0.)));
+
+        // Neither the clip nor the transform applies inside the target
+        draw_context.push_render_target(target, Some(white()));
+        draw_context.draw_rect(0., 0., 32., 32., red());
+        draw_context.pop_render_target();
+
+        draw_context.draw_rect(0., 64., 32., 32., red());
+        draw_context.pop_transform();
+        draw_context.pop_clip_rect();
+
+        draw_context.draw_texture(0., 96., 32., 32., target.texture_area(), white());
+    };
+
+    // Transformed to the right, then clipped away
+    assert_eq!(target_pixel(draw, 80, 80), [0, 0, 0, 255]);
+    assert_eq!(target_pixel(draw, 16, 80), [0, 0, 0, 255]);
+    assert_eq!(target_pixel(draw, 16, 112), [255, 0, 0, 255]);
+}
+
+#[test]
+fn clip_applies_inside_the_target() {
+    let draw = |draw_context: &mut DrawContext, target| {
+        draw_context.push_render_target(target, Some(white()));
+        // Near the bottom, so a clip flipped the wrong way misses it
+        draw_context.push_clip_rect(4., 20., 8., 8.);
+        draw_context.draw_rect(0., 0., 32., 32., red());
+        draw_context.pop_clip_rect();
+        draw_context.pop_render_target();
+
+        draw_context.draw_texture(0., 0., 32., 32., target.texture_area(), white());
+    };
+
+    assert_eq!(target_pixel(draw, 8, 24), [255, 0, 0, 255]);
+    assert_eq!(target_pixel(draw, 8, 8), [255, 255, 255, 255]);
+    assert_eq!(target_pixel(draw, 20, 24), [255, 255, 255, 255]);
+}
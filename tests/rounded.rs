@@ -0,0 +1,90 @@
+mod common;
+
+use common::{draw_pixel, snapshot};
+use porcelain::{
+    Rect,
+    color::Color,
+    render::{BorderWidths, CornerRadii, DrawContext},
+};
+
+fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect {
+    Rect { x, y, w, h }
+}
+
+#[test]
+fn rounded_rect_per_corner_radii() {
+    snapshot("rounded_rect", |draw_context| {
+        draw_context.draw_rounded_rect(
+            rect(8., 8., 112., 48.),
+            CornerRadii {
+                top_left: 0.,
+                top_right: 8.,
+                bottom_right: 24.,
+                bottom_left: 4.,
+            },
+            Color::from_rgba8(80, 160, 240, 255),
+        );
+        // Radii larger than the rectangle are scaled down into a pill
+        draw_context.draw_rounded_rect(
+            rect(8., 72., 112., 40.),
+            CornerRadii::all(100.),
+            Color::from_rgba8(240, 160, 80, 255),
+        );
+    });
+}
+
+#[test]
+fn rounded_rect_borders() {
+    snapshot("rounded_rect_border", |draw_context| {
+        draw_context.draw_rounded_rect_border(
+            rect(8., 8., 112., 48.),
+            CornerRadii::all(12.),
+            BorderWidths::all(3.),
+            Color::from_rgba8(255, 255, 255, 255),
+        );
+        draw_context.draw_rounded_rect_bordered(
+            rect(8., 68., 112., 52.),
+            CornerRadii::all(16.),
+            BorderWidths {
+                left: 12.,
+                right: 2.,
+                top: 2.,
+                bottom: 6.,
+            },
+            Color::from_rgba8(40, 120, 80, 255),
+            Color::from_rgba8(220, 220, 120, 255),
+        );
+    });
+}
+
+#[test]
+fn corners_are_cut_and_inside_is_filled() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.draw_rounded_rect(
+            rect(0., 0., 64., 64.),
+            CornerRadii::all(20.),
+            Color::from_rgba8(255, 0, 0, 255),
+        );
+    };
+
+    assert_eq!(draw_pixel(draw, 1, 1), [0, 0, 0, 255]);
+    assert_eq!(draw_pixel(draw, 62, 62), [0, 0, 0, 255]);
+    assert_eq!(draw_pixel(draw, 32, 1), [255, 0, 0, 255]);
+    assert_eq!(draw_pixel(draw, 32, 32), [255, 0, 0, 255]);
+}
+
+#[test]
+fn border_only_leaves_the_middle_empty() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.draw_rounded_rect_border(
+            rect(0., 0., 64., 64.),
+            CornerRadii::all(8.),
+            BorderWidths::all(4.),
+            Color::from_rgba8(255, 0, 0, 255),
+        );
+    };
+
+    assert_eq!(draw_pixel(draw, 32, 2), [255, 0, 0, 255]);
+    assert_eq!(draw_pixel(draw, 2, 32), [255, 0, 0, 255]);
+    assert_eq!(draw_pixel(draw, 32, 32), [0, 0, 0, 255]);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::{draw_pixel, snapshot};
+use glam::Vec2;
+use porcelain::{
+    Rect,
+    color::Color,
+    render::{CornerRadii, DrawContext},
+};
+
+fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect {
+    Rect { x, y, w, h }
+}
+
+fn white() -> Color {
+    Color::from_rgba8(255, 255, 255, 255)
+}
+
+#[test]
+fn cards_with_shadows() {
+    snapshot("box_shadow", |draw_context| {
+        draw_context.draw_rect(0., 0., 128., 128., Color::from_rgba8(230, 230, 235, 255));
+
+        draw_context.draw_box_shadow(
+            rect(16., 16., 40., 40.),
+            8.,
+            12.,
+            0.,
+            Vec2::new(0., 4.),
+            Color::from_rgba8(0, 0, 0, 128),
+        );
+        draw_context.draw_rounded_rect(rect(16., 16., 40., 40.), CornerRadii::all(8.), white());
+
+        draw_context.draw_box_shadow(
+            rect(72., 16., 40., 40.),
+            0.,
+            0.,
+            4.,
+            Vec2::new(4., 4.),
+            Color::from_rgba8(40, 60, 200, 255),
+        );
+        draw_context.draw_rect(72., 16., 40., 40., white());
+
+        draw_context.draw_box_shadow(
+            rect(16., 72., 96., 40.),
+            20.,
+            24.,
+            -4.,
+            Vec2::ZERO,
+            Color::from_rgba8(200, 40, 40, 255),
+        );
+    });
+}
+
+#[test]
+fn blur_fades_across_the_edge() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.draw_box_shadow(rect(32., 32., 64., 64.), 0., 16., 0., Vec2::ZERO, white());
+    };
+
+    let [center, ..] = draw_pixel(draw, 64, 64);
+    let [edge, ..] = draw_pixel(draw, 32, 64);
+    let [outside, ..] = draw_pixel(draw, 8, 64);
+    assert!(center > 250, "{center}");
+    assert!((edge as i32 - 128).abs() < 8, "{edge}");
+    assert!(outside < 10, "{outside}");
+}
+
+#[test]
+fn no_blur_gives_a_sharp_edge() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.draw_box_shadow(
+            rect(32., 32., 64., 64.),
+            0.,
+            0.,
+            8.,
+            Vec2::new(0., 8.),
+            white(),
+        );
+    };
+
+    // Grown by 8 on every side, then moved down by 8. The corners are rounded by the spread,
+    // as with CSS, so only look at the middle of the edges
+    assert_eq!(draw_pixel(draw, 24, 72), [255, 255, 255, 255]);
+    assert_eq!(draw_pixel(draw, 23, 72), [0, 0, 0, 255]);
+    assert_eq!(draw_pixel(draw, 64, 111), [255, 255, 255, 255]);
+    assert_eq!(draw_pixel(draw, 64, 112), [0, 0, 0, 255]);
+}
+
+#[test]
+fn shapes_drawn_after_a_shadow_cover_it() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.draw_box_shadow(rect(32., 32., 64., 64.), 8., 8., 0., Vec2::ZERO, white());
+        draw_context.draw_rect(48., 48., 32., 32., Color::from_rgba8(255, 0, 0, 255));
+        draw_context.draw_box_shadow(rect(60., 60., 8., 8.), 0., 0., 0., Vec2::ZERO, white());
+    };
+
+    assert_eq!(draw_pixel(draw, 50, 50), [255, 0, 0, 255]);
+    assert_eq!(draw_pixel(draw, 64, 64), [255, 255, 255, 255]);
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::{golden, scene, scene_with, snapshot};
+use porcelain::{
+    Point,
+    color::Color,
+    render::{DrawContext, TextureArea},
+    snapshot::{SnapshotConfig, assert_snapshot},
+    texture::TextureContext,
+};
+
+use glam::Vec2;
+use miniquad::TextureId;
+
+#[test]
+fn rect() {
+    snapshot("rect", |draw_context| {
+        draw_context.draw_rect(16., 24., 64., 40., Color::from_rgba8(255, 0, 0, 255));
+        draw_context.draw_rect(56., 56., 48., 48., Color::from_rgba8(0, 128, 255, 255));
+    });
+}
+
+#[test]
+fn rect_ext() {
+    snapshot("rect_ext", |draw_context| {
+        draw_context.draw_rect_ext(
+            &Point { x: 64., y: 64. },
+            80.,
+            40.,
+            30.,
+            Color::from_rgba8(255, 192, 0, 255),
+        );
+    });
+}
+
+#[test]
+fn poly_ext() {
+    snapshot("poly_ext", |draw_context| {
+        draw_context.draw_poly(
+            &Point { x: 36., y: 36. },
+            28.,
+            3,
+            Color::from_rgba8(56, 122, 243, 255),
+        );
+        draw_context.draw_poly_ext(
+            &Point { x: 92., y: 92. },
+            28.,
+            6,
+            15.,
+            Color::from_rgba8(182, 35, 133, 255),
+        );
+    });
+}
+
+#[test]
+fn circle() {
+    snapshot("circle", |draw_context| {
+        draw_context.draw_circle(
+            &Point { x: 64., y: 64. },
+            48.,
+            Color::from_rgba8(128, 128, 192, 255),
+        );
+    });
+}
+
+#[test]
+fn circle_arc() {
+    snapshot("circle_arc", |draw_context| {
+        draw_context.draw_circle_arc(
+            &Point { x: 36., y: 64. },
+            28.,
+            90.,
+            180.,
+            Color::from_rgba8(92, 16, 173, 255),
+        );
+        // Arcs over 180 degrees must not be closed back to their start
+        draw_context.draw_circle_arc(
+            &Point { x: 96., y: 64. },
+            28.,
+            0.,
+            270.,
+            Color::from_rgba8(16, 173, 92, 255),
+        );
+    });
+}
+
+#[test]
+fn line() {
+    snapshot("line", |draw_context| {
+        draw_context.draw_line(
+            &Point { x: 16., y: 16. },
+            &Point { x: 112., y: 96. },
+            8.,
+            Color::from_rgba8(125, 213, 129, 255),
+        );
+        draw_context.draw_line(
+            &Point { x: 16., y: 112. },
+            &Point { x: 112., y: 112. },
+            4.,
+            Color::from_rgba8(255, 255, 255, 255),
+        );
+    });
+}
+
+#[test]
+fn texture() {
+    let checkerboard = scene_with(
+        |texture_context: &TextureContext| {
+            #[rustfmt::skip]
+            let pixels = [
+                255, 255, 255, 255,     0,   0,   0, 255,
+                  0,   0,   0, 255,   255, 255, 255, 255,
+            ];
+            texture_context.register_texture_rgba8(2, 2, &pixels)
+        },
+        |draw_context: &mut DrawContext, texture: &TextureId| {
+            draw_context.draw_texture(
+                16.,
+                16.,
+                96.,
+                96.,
+                TextureArea {
+                    texture: *texture,
+                    location: Vec2::ZERO,
+                    size: Vec2::ONE,
+                },
+                Color::from_rgba8(255, 128, 128, 255),
+            );
+        },
+    );
+
+    assert_snapshot(golden("texture"), &SnapshotConfig::default(), checkerboard);
+}
+
+#[test]
+#[should_panic(expected = "is missing; actual frame written to")]
+fn missing_golden_fails() {
+    let golden_path = std::env::temp_dir()
+        .join("porcelain-missing-golden")
+        .join("missing.png");
+
+    assert_snapshot(golden_path, &SnapshotConfig::default(), scene(|_| {}));
+}
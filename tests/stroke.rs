@@ -0,0 +1,160 @@
+mod common;
+
+use common::{draw_pixel, pixel, scene, snapshot};
+use porcelain::{
+    Point,
+    color::Color,
+    render::{DrawContext, LineCap, LineJoin, StrokeStyle},
+    snapshot::{SnapshotConfig, render},
+};
+
+fn pixel_with(
+    config: &SnapshotConfig,
+    draw: impl Fn(&mut DrawContext) + 'static,
+    x: usize,
+    y: usize,
+) -> [u8; 4] {
+    pixel(&render(config, scene(draw)), x, y)
+}
+
+fn points(points: &[(f32, f32)]) -> Vec<Point> {
+    points.iter().map(|&(x, y)| Point { x, y }).collect()
+}
+
+fn style(thickness: f32, join: LineJoin, cap: LineCap) -> StrokeStyle {
+    StrokeStyle {
+        thickness,
+        join,
+        cap,
+        ..Default::default()
+    }
+}
+
+fn red() -> Color {
+    Color::from_rgba8(255, 0, 0, 255)
+}
+
+#[test]
+fn joins_and_caps() {
+    snapshot("stroke_joins_and_caps", |draw_context| {
+        let zigzag = [(12., 40.), (28., 12.), (44., 40.)];
+        for (i, (join, cap)) in [
+            (LineJoin::Miter, LineCap::Butt),
+            (LineJoin::Round, LineCap::Round),
+            (LineJoin::Bevel, LineCap::Square),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let offset = i as f32 * 38.;
+            let line: Vec<(f32, f32)> = zigzag.iter().map(|&(x, y)| (x + offset, y)).collect();
+            draw_context.draw_polyline(
+                &points(&line),
+                false,
+                style(8., join, cap),
+                Color::from_rgba8(240, 200, 80, 255),
+            );
+        }
+
+        draw_context.draw_polyline(
+            &points(&[(12., 60.), (60., 60.), (60., 76.), (36., 100.), (12., 76.)]),
+            true,
+            style(4., LineJoin::Miter, LineCap::Butt),
+            Color::from_rgba8(80, 200, 240, 255),
+        );
+        draw_context.draw_poly_lines(
+            &Point { x: 96., y: 80. },
+            20.,
+            6,
+            0.,
+            3.,
+            Color::from_rgba8(200, 80, 240, 255),
+        );
+        draw_context.draw_circle_lines(
+            &Point { x: 96., y: 80. },
+            10.,
+            2.,
+            Color::from_rgba8(80, 240, 120, 255),
+        );
+    });
+}
+
+#[test]
+fn square_caps_extend_past_the_end_points() {
+    let line = |cap| {
+        move |draw_context: &mut DrawContext| {
+            draw_context.draw_polyline(
+                &points(&[(20., 20.), (60., 20.)]),
+                false,
+                style(8., LineJoin::Miter, cap),
+                red(),
+            );
+        }
+    };
+
+    assert_eq!(draw_pixel(line(LineCap::Butt), 62, 20), [0, 0, 0, 255]);
+    assert_eq!(draw_pixel(line(LineCap::Square), 62, 20), [255, 0, 0, 255]);
+    assert_eq!(draw_pixel(line(LineCap::Round), 62, 20), [255, 0, 0, 255]);
+}
+
+#[test]
+fn miter_joins_fill_the_outer_corner() {
+    let corner = |join| {
+        move |draw_context: &mut DrawContext| {
+            draw_context.draw_polyline(
+                &points(&[(20., 20.), (60., 20.), (60., 60.)]),
+                false,
+                style(8., join, LineCap::Butt),
+                red(),
+            );
+        }
+    };
+
+    assert_eq!(
+        draw_pixel(corner(LineJoin::Miter), 63, 16),
+        [255, 0, 0, 255]
+    );
+    assert_eq!(draw_pixel(corner(LineJoin::Bevel), 63, 16), [0, 0, 0, 255]);
+}
+
+#[test]
+fn translucent_joins_do_not_overlap() {
+    let corner = |join| {
+        move |draw_context: &mut DrawContext| {
+            draw_context.draw_polyline(
+                &points(&[(20., 20.), (60., 20.), (60., 60.)]),
+                false,
+                style(8., join, LineCap::Butt),
+                Color::from_rgba8(255, 0, 0, 128),
+            );
+        }
+    };
+
+    for join in [LineJoin::Miter, LineJoin::Round, LineJoin::Bevel] {
+        let segment = draw_pixel(corner(join), 40, 20);
+        assert_eq!(draw_pixel(corner(join), 57, 22), segment);
+        assert_eq!(draw_pixel(corner(join), 62, 18), segment);
+    }
+}
+
+#[test]
+fn long_polylines_are_split_over_several_calls() {
+    let config = SnapshotConfig {
+        max_vertices_per_draw: 64,
+        max_indices_per_draw: 96,
+        ..Default::default()
+    };
+    let zigzag: Vec<(f32, f32)> = (0..100)
+        .map(|i| (8. + i as f32 * 1.1, if i % 2 == 0 { 40. } else { 60. }))
+        .collect();
+    let draw = move |draw_context: &mut DrawContext| {
+        draw_context.draw_polyline(
+            &points(&zigzag),
+            false,
+            style(2., LineJoin::Round, LineCap::Round),
+            red(),
+        );
+    };
+
+    assert_eq!(pixel_with(&config, draw, 116, 50), [255, 0, 0, 255]);
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::{golden, scene_with};
+use miniquad::EventHandler;
+use porcelain::{
+    backend::SoftwareBackend,
+    color::Color,
+    conf::WindowConfig,
+    render::{DrawContext, EventListener, RendererContext},
+    snapshot::{SnapshotConfig, assert_snapshot},
+    text::FontId,
+    texture::TextureContext,
+};
+
+const FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSansMono.ttf");
+
+/// Loads the bundled font on update and passes it to `draw`.
+fn font_scene(draw: impl Fn(&mut DrawContext, FontId) + 'static) -> impl EventListener + 'static {
+    scene_with(
+        |texture_context: &TextureContext| {
+            texture_context
+                .load_font(FONT)
+                .expect("Bundled font is valid")
+        },
+        move |draw_context: &mut DrawContext, font: &FontId| draw(draw_context, *font),
+    )
+}
+
+#[test]
+fn text() {
+    let label = font_scene(|draw_context, font| {
+        draw_context.draw_rect(0., 0., 128., 32., Color::from_rgba8(40, 60, 120, 255));
+        draw_context.draw_text(
+            "Hello,\nporcelain!",
+            8.,
+            4.,
+            font,
+            14.,
+            Color::from_rgba8(255, 255, 255, 255),
+        );
+        draw_context.draw_text(
+            "AVA",
+            8.,
+            80.,
+            font,
+            32.,
+            Color::from_rgba8(255, 200, 0, 255),
+        );
+    });
+
+    assert_snapshot(golden("text"), &SnapshotConfig::default(), label);
+}
+
+/// Pixels of a 96x48 frame drawn by `draw` at `dpi_scale` physical pixels per unit.
+fn render_scaled(dpi_scale: f32, draw: impl Fn(&mut DrawContext, FontId) + 'static) -> Vec<u8> {
+    let mut renderer = RendererContext::with_backend(
+        WindowConfig::default(),
+        font_scene(draw),
+        SoftwareBackend::new(96, 48).with_dpi_scale(dpi_scale),
+    );
+    renderer.update();
+    renderer.draw();
+
+    renderer.backend().pixels().to_vec()
+}
+
+#[test]
+fn high_dpi_text_is_rasterized_at_physical_size() {
+    let white = Color::from_rgba8(255, 255, 255, 255);
+    let scaled = render_scaled(2., move |draw_context, font| {
+        draw_context.draw_text("Sharp", 2.5, 3., font, 11., white);
+    });
+    let physical = render_scaled(1., move |draw_context, font| {
+        draw_context.draw_text("Sharp", 5., 6., font, 22., white);
+    });
+
+    assert!(scaled.chunks(4).any(|pixel| pixel == [255, 255, 255, 255]));
+    assert!(
+        scaled == physical,
+        "Glyphs were resampled from a smaller bitmap"
+    );
+}
+
+#[test]
+fn glyphs_larger_than_a_page_are_skipped_without_a_page() {
+    let texture_count = |draw: fn(&mut DrawContext, FontId)| {
+        let mut renderer = RendererContext::with_backend(
+            WindowConfig::default(),
+            font_scene(draw),
+            SoftwareBackend::new(96, 48),
+        );
+        renderer.update();
+        renderer.draw();
+
+        renderer.backend().texture_count()
+    };
+
+    let nothing = texture_count(|_, _| {});
+    let huge = texture_count(|draw_context, font| {
+        draw_context.draw_text(
+            "M",
+            0.,
+            0.,
+            font,
+            4000.,
+            Color::from_rgba8(255, 255, 255, 255),
+        );
+    });
+
+    assert_eq!(huge, nothing);
+}
+
+#[test]
+fn measure_text() {
+    let measure = font_scene(|draw_context, font| {
+        let one_line = draw_context.measure_text("ab", font, 20.);
+        let two_lines = draw_context.measure_text("ab\nabcd", font, 20.);
+
+        // Monospace, so the longest line is exactly twice as wide
+        assert!((two_lines.width - one_line.width * 2.).abs() < 0.01);
+        assert!(two_lines.height > one_line.height * 1.5);
+        assert!(one_line.ascent > 0. && one_line.ascent < one_line.height);
+    });
+
+    porcelain::snapshot::render(&SnapshotConfig::default(), measure);
+}
+
+#[test]
+fn invalid_font_is_an_error() {
+    let invalid = scene_with(
+        |texture_context: &TextureContext| {
+            assert!(texture_context.load_font(&[0, 1, 2, 3]).is_err());
+        },
+        |_: &mut DrawContext, _: &()| {},
+    );
+
+    porcelain::snapshot::render(&SnapshotConfig::default(), invalid);
+}
@@ -0,0 +1,286 @@
+mod common;
+
+use common::{golden, pixel, scene_with};
+use glam::Vec2;
+use porcelain::{
+    color::Color,
+    render::{DrawContext, TextureArea},
+    snapshot::{Image, SnapshotConfig, assert_snapshot},
+    texture::{
+        FilterMode, RenderTarget, Texture, TextureContext, TextureFormat, TextureOptions,
+        TextureWrap,
+    },
+};
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+/// Creates a texture with `setup` on update and passes it to the draw closure.
+fn render(
+    setup: impl Fn(&TextureContext) -> Texture + 'static,
+    draw: impl Fn(&mut DrawContext, Texture) + 'static,
+) -> Image {
+    porcelain::snapshot::render(
+        &SnapshotConfig::default(),
+        scene_with(setup, move |draw_context, texture: &Texture| {
+            draw(draw_context, *texture)
+        }),
+    )
+}
+
+fn white() -> Color {
+    Color::from_rgba8(255, 255, 255, 255)
+}
+
+fn nearest(wrap: TextureWrap) -> TextureOptions {
+    TextureOptions {
+        filter: FilterMode::Nearest,
+        wrap,
+        mipmaps: false,
+    }
+}
+
+/// Red, green, blue and white pixels, in reading order.
+fn checker(texture_context: &TextureContext, options: TextureOptions) -> Texture {
+    texture_context.create_texture(
+        2,
+        2,
+        TextureFormat::RGBA8,
+        &[RED, GREEN, BLUE, WHITE].concat(),
+        options,
+    )
+}
+
+/// The texture drawn over 0..64 with its coordinates going from 0 to `repeat`.
+fn draw_repeated(draw_context: &mut DrawContext, texture: Texture, repeat: f32) {
+    let area = TextureArea {
+        size: Vec2::splat(repeat),
+        ..texture.texture_area()
+    };
+    draw_context.draw_texture(0., 0., 64., 64., area, white());
+}
+
+#[test]
+fn nearest_filter_keeps_pixels_sharp() {
+    let image = render(
+        |texture_context| checker(texture_context, nearest(TextureWrap::Clamp)),
+        |draw_context, texture| draw_repeated(draw_context, texture, 1.),
+    );
+
+    assert_eq!(pixel(&image, 31, 31), RED);
+    assert_eq!(pixel(&image, 32, 31), GREEN);
+    assert_eq!(pixel(&image, 31, 32), BLUE);
+    assert_eq!(pixel(&image, 32, 32), WHITE);
+}
+
+#[test]
+fn linear_filter_blends_neighbours() {
+    let image = render(
+        |texture_context| checker(texture_context, TextureOptions::default()),
+        |draw_context, texture| draw_repeated(draw_context, texture, 1.),
+    );
+
+    let [r, g, _, _] = pixel(&image, 32, 8);
+    assert!(
+        r > 64 && g > 64,
+        "expected red and green mixed, got {r} {g}"
+    );
+}
+
+#[test]
+fn repeat_wrap_tiles_texture() {
+    let image = render(
+        |texture_context| checker(texture_context, nearest(TextureWrap::Repeat)),
+        |draw_context, texture| draw_repeated(draw_context, texture, 2.),
+    );
+
+    assert_eq!(pixel(&image, 8, 8), RED);
+    assert_eq!(pixel(&image, 24, 8), GREEN);
+    assert_eq!(pixel(&image, 40, 8), RED);
+    assert_eq!(pixel(&image, 56, 8), GREEN);
+    assert_eq!(pixel(&image, 40, 40), RED);
+}
+
+#[test]
+fn mirror_wrap_reflects_texture() {
+    let image = render(
+        |texture_context| checker(texture_context, nearest(TextureWrap::Mirror)),
+        |draw_context, texture| draw_repeated(draw_context, texture, 2.),
+    );
+
+    assert_eq!(pixel(&image, 8, 8), RED);
+    assert_eq!(pixel(&image, 24, 8), GREEN);
+    assert_eq!(pixel(&image, 40, 8), GREEN);
+    assert_eq!(pixel(&image, 56, 8), RED);
+    assert_eq!(pixel(&image, 56, 56), RED);
+}
+
+#[test]
+fn clamp_wrap_stretches_edges() {
+    let image = render(
+        |texture_context| checker(texture_context, nearest(TextureWrap::Clamp)),
+        |draw_context, texture| draw_repeated(draw_context, texture, 2.),
+    );
+
+    assert_eq!(pixel(&image, 8, 8), RED);
+    assert_eq!(pixel(&image, 56, 8), GREEN);
+    assert_eq!(pixel(&image, 56, 56), WHITE);
+}
+
+#[test]
+fn wrap_modes() {
+    assert_snapshot(
+        golden("texture_wrap"),
+        &SnapshotConfig::default(),
+        scene_with(
+            |texture_context: &TextureContext| {
+                checker(texture_context, nearest(TextureWrap::Repeat))
+            },
+            |draw_context: &mut DrawContext, texture: &Texture| {
+                let area = TextureArea {
+                    location: Vec2::splat(-0.5),
+                    size: Vec2::splat(3.),
+                    ..texture.texture_area()
+                };
+                draw_context.draw_texture(16., 16., 96., 96., area, white());
+            },
+        ),
+    );
+}
+
+#[test]
+fn update_texture_part_changes_only_that_region() {
+    let image = render(
+        |texture_context| {
+            let texture = checker(texture_context, nearest(TextureWrap::Clamp));
+            texture_context.update_texture_part(&texture, 1, 1, 1, 1, &BLUE);
+            texture
+        },
+        |draw_context, texture| draw_repeated(draw_context, texture, 1.),
+    );
+
+    assert_eq!(pixel(&image, 8, 8), RED);
+    assert_eq!(pixel(&image, 40, 8), GREEN);
+    assert_eq!(pixel(&image, 8, 40), BLUE);
+    assert_eq!(pixel(&image, 40, 40), BLUE);
+}
+
+#[test]
+fn update_texture_replaces_every_pixel() {
+    let image = render(
+        |texture_context| {
+            let texture = checker(texture_context, nearest(TextureWrap::Clamp));
+            texture_context.update_texture(&texture, &[WHITE, BLUE, GREEN, RED].concat());
+            texture
+        },
+        |draw_context, texture| draw_repeated(draw_context, texture, 1.),
+    );
+
+    assert_eq!(pixel(&image, 8, 8), WHITE);
+    assert_eq!(pixel(&image, 40, 8), BLUE);
+    assert_eq!(pixel(&image, 8, 40), GREEN);
+    assert_eq!(pixel(&image, 40, 40), RED);
+}
+
+#[test]
+fn rgb8_updates_use_the_texture_format() {
+    let image = render(
+        |texture_context| {
+            let texture = texture_context.create_texture(
+                1,
+                1,
+                TextureFormat::RGB8,
+                &[255, 0, 0],
+                TextureOptions::default(),
+            );
+            texture_context.update_texture(&texture, &[0, 0, 255]);
+            texture
+        },
+        |draw_context, texture| draw_repeated(draw_context, texture, 1.),
+    );
+
+    assert_eq!(pixel(&image, 8, 8), BLUE);
+}
+
+#[test]
+fn texture_reports_size_and_format() {
+    render(
+        |texture_context| {
+            let texture = texture_context.create_texture(
+                3,
+                2,
+                TextureFormat::RGB8,
+                &[0; 18],
+                TextureOptions::default(),
+            );
+            assert_eq!(texture.width(), 3);
+            assert_eq!(texture.height(), 2);
+            assert_eq!(texture.format(), TextureFormat::RGB8);
+            texture
+        },
+        |_, _| {},
+    );
+}
+
+#[test]
+#[should_panic(expected = "Texture was not created by this backend")]
+fn deleted_texture_cannot_be_drawn() {
+    render(
+        |texture_context| {
+            let texture = checker(texture_context, TextureOptions::default());
+            texture_context.delete_texture(texture);
+            texture
+        },
+        |draw_context, texture| draw_repeated(draw_context, texture, 1.),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Texture was not created by this backend")]
+fn deleted_render_target_cannot_be_drawn() {
+    porcelain::snapshot::render(
+        &SnapshotConfig::default(),
+        scene_with(
+            |texture_context: &TextureContext| {
+                let target = texture_context.create_render_target(4, 4);
+                texture_context.delete_render_target(target);
+                target
+            },
+            |draw_context: &mut DrawContext, target: &RenderTarget| {
+                draw_context.draw_texture(0., 0., 4., 4., target.texture_area(), white());
+            },
+        ),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Expected 2 * 2 * 3 = 12 bytes, got 16 bytes in buffer")]
+fn create_texture_checks_buffer_size() {
+    render(
+        |texture_context| {
+            texture_context.create_texture(
+                2,
+                2,
+                TextureFormat::RGB8,
+                &[0; 16],
+                TextureOptions::default(),
+            )
+        },
+        |_, _| {},
+    );
+}
+
+#[test]
+#[should_panic(expected = "Area 2 * 1 at (1, 0) is outside of the 2 * 2 texture")]
+fn update_texture_part_checks_bounds() {
+    render(
+        |texture_context| {
+            let texture = checker(texture_context, TextureOptions::default());
+            texture_context.update_texture_part(&texture, 1, 0, 2, 1, &[RED, RED].concat());
+            texture
+        },
+        |_, _| {},
+    );
+}
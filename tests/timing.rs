@@ -0,0 +1,232 @@
+use std::{cell::RefCell, rc::Rc, time::Instant};
+
+use miniquad::EventHandler;
+use porcelain::{
+    Error,
+    backend::SoftwareBackend,
+    conf::WindowConfig,
+    render::{DrawContext, EventListener, RendererContext},
+    texture::TextureContext,
+    timing::{FrameSteps, FrameTimer},
+};
+
+fn fixed(rate: f64) -> WindowConfig {
+    WindowConfig {
+        fixed_update_rate: Some(rate),
+        ..Default::default()
+    }
+}
+
+/// Fixed updates run by frames at `timestamps`.
+fn simulate(config: &WindowConfig, timestamps: &[f64]) -> u32 {
+    let mut timer = FrameTimer::new(config, 0.);
+
+    timestamps.iter().map(|&now| timer.tick(now).updates).sum()
+}
+
+/// The `dt` of every update and the interpolation alpha of every draw.
+#[derive(Default)]
+struct Frames {
+    dts: Vec<f64>,
+    alphas: Vec<f32>,
+}
+
+struct Recorder(Rc<RefCell<Frames>>);
+
+impl EventListener for Recorder {
+    fn update(&mut self, _texture_context: &TextureContext, dt: f64) {
+        self.0.borrow_mut().dts.push(dt);
+    }
+
+    fn draw(&self, draw_context: &mut DrawContext) {
+        self.0
+            .borrow_mut()
+            .alphas
+            .push(draw_context.interpolation_alpha());
+    }
+}
+
+fn renderer(
+    config: WindowConfig,
+) -> (
+    RendererContext<Recorder, SoftwareBackend>,
+    Rc<RefCell<Frames>>,
+) {
+    let frames = Rc::new(RefCell::new(Frames::default()));
+    let renderer = RendererContext::with_backend(
+        config,
+        Recorder(frames.clone()),
+        SoftwareBackend::new(16, 16),
+    );
+
+    (renderer, frames)
+}
+
+#[test]
+fn variable_steps_follow_the_clock() {
+    let config = WindowConfig {
+        max_dt: None,
+        ..Default::default()
+    };
+    let mut timer = FrameTimer::new(&config, 10.);
+
+    assert_eq!(
+        timer.tick(10.25),
+        FrameSteps {
+            updates: 1,
+            dt: 0.25
+        }
+    );
+    assert_eq!(timer.tick(12.25).dt, 2.);
+    assert_eq!(timer.alpha(), 1.);
+}
+
+#[test]
+fn max_dt_clamps_long_frames() {
+    let config = WindowConfig {
+        max_dt: Some(0.1),
+        ..Default::default()
+    };
+    let mut timer = FrameTimer::new(&config, 0.);
+
+    assert_eq!(timer.tick(5.).dt, 0.1);
+    assert!((timer.tick(5.05).dt - 0.05).abs() < 1e-9);
+}
+
+#[test]
+fn fixed_steps_accumulate_across_frames() {
+    let mut timer = FrameTimer::new(&fixed(8.), 0.);
+
+    assert_eq!(timer.tick(0.0625).updates, 0);
+    assert_eq!(timer.alpha(), 0.5);
+
+    let steps = timer.tick(0.3125);
+    assert_eq!(
+        steps,
+        FrameSteps {
+            updates: 2,
+            dt: 0.125
+        }
+    );
+    assert_eq!(timer.alpha(), 0.5);
+}
+
+#[test]
+fn fixed_steps_do_not_depend_on_frame_times() {
+    let config = fixed(60.);
+    let steady: Vec<f64> = (1..=120).map(|frame| frame as f64 / 60.).collect();
+    let jittery: Vec<f64> = (1..=60)
+        .map(|frame| frame as f64 / 30. - if frame % 3 == 0 { 0.011 } else { 0. })
+        .chain([2.])
+        .collect();
+
+    assert_eq!(simulate(&config, &steady), 120);
+    assert_eq!(simulate(&config, &jittery), 120);
+}
+
+#[test]
+fn max_dt_bounds_fixed_updates_after_a_stall() {
+    let config = WindowConfig {
+        max_dt: Some(0.25),
+        ..fixed(60.)
+    };
+
+    assert_eq!(simulate(&config, &[30.]), 15);
+}
+
+#[test]
+fn a_single_huge_frame_is_bounded_by_default() {
+    let mut timer = FrameTimer::new(&WindowConfig::default(), 0.);
+    assert_eq!(timer.tick(1e9).dt, 0.25);
+
+    let mut timer = FrameTimer::new(&fixed(60.), 0.);
+    assert_eq!(timer.tick(1e9).updates, 15);
+    assert_eq!(timer.tick(1e9 + 0.02).updates, 1);
+}
+
+#[test]
+fn wait_time_reaches_the_target_frame_time() {
+    let config = WindowConfig {
+        target_fps: Some(10.),
+        ..Default::default()
+    };
+    let mut timer = FrameTimer::new(&config, 1.);
+
+    assert!((timer.wait_time(1.04) - 0.06).abs() < 1e-9);
+    timer.tick(1.1);
+    assert_eq!(timer.wait_time(1.3), 0.);
+    assert_eq!(
+        FrameTimer::new(&WindowConfig::default(), 1.).wait_time(1.),
+        0.
+    );
+}
+
+#[test]
+fn timing_options_are_validated() {
+    for config in [
+        fixed(0.),
+        WindowConfig {
+            max_dt: Some(f64::NAN),
+            ..Default::default()
+        },
+        WindowConfig {
+            target_fps: Some(-30.),
+            ..Default::default()
+        },
+        WindowConfig {
+            swap_interval: Some(-1),
+            ..Default::default()
+        },
+    ] {
+        assert!(matches!(config.validate(), Err(Error::InvalidConfig(_))));
+    }
+
+    assert_eq!(
+        fixed(0.).validate().unwrap_err().to_string(),
+        "Invalid window config: fixed_update_rate must be positive, got 0"
+    );
+    assert!(
+        WindowConfig {
+            swap_interval: Some(0),
+            ..fixed(120.)
+        }
+        .validate()
+        .is_ok()
+    );
+}
+
+#[test]
+fn renderer_skips_updates_until_a_fixed_step_elapsed() {
+    let (mut renderer, frames) = renderer(fixed(1.));
+
+    renderer.update();
+    renderer.draw();
+
+    assert!(frames.borrow().dts.is_empty());
+    assert!(frames.borrow().alphas[0] < 1.);
+}
+
+#[test]
+fn renderer_draws_with_full_alpha_without_fixed_steps() {
+    let (mut renderer, frames) = renderer(WindowConfig::default());
+
+    renderer.update();
+    renderer.draw();
+
+    assert_eq!(frames.borrow().dts.len(), 1);
+    assert_eq!(frames.borrow().alphas, [1.]);
+}
+
+#[test]
+fn renderer_waits_for_the_target_fps() {
+    let (mut renderer, frames) = renderer(WindowConfig {
+        target_fps: Some(20.),
+        ..Default::default()
+    });
+
+    let start = Instant::now();
+    renderer.update();
+
+    assert!(start.elapsed().as_secs_f64() >= 0.04);
+    assert!(frames.borrow().dts[0] >= 0.04);
+}
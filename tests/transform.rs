@@ -0,0 +1,66 @@
+mod common;
+
+use common::{draw_pixel, snapshot};
+use glam::{Affine2, Vec2};
+use porcelain::{Point, color::Color, render::DrawContext};
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+#[test]
+fn transforms_compose_and_pop() {
+    let draw = |draw_context: &mut DrawContext| {
+        draw_context.push_transform(Affine2::from_translation(Vec2::new(40., 0.)));
+        draw_context.push_transform(Affine2::from_scale(Vec2::splat(2.)));
+        // Lands on (40, 0) to (60, 20)
+        draw_context.draw_rect(0., 0., 10., 10., Color::from_rgba8(255, 0, 0, 255));
+        draw_context.pop_transform();
+        draw_context.pop_transform();
+
+        draw_context.draw_rect(0., 100., 10., 10., Color::from_rgba8(255, 0, 0, 255));
+    };
+
+    assert_eq!(draw_pixel(draw, 55, 15), RED);
+    assert_eq!(draw_pixel(draw, 5, 5), BLACK);
+    assert_eq!(draw_pixel(draw, 5, 105), RED);
+}
+
+#[test]
+fn every_primitive_is_transformed() {
+    snapshot("transform", |draw_context| {
+        draw_context.push_transform(Affine2::from_scale_angle_translation(
+            Vec2::splat(0.75),
+            30_f32.to_radians(),
+            Vec2::new(64., 16.),
+        ));
+
+        draw_context.draw_rect(0., 0., 40., 20., Color::from_rgba8(255, 96, 0, 255));
+        draw_context.draw_rect_ext(
+            &Point { x: 20., y: 60. },
+            40.,
+            20.,
+            45.,
+            Color::from_rgba8(0, 160, 255, 255),
+        );
+        draw_context.draw_poly(
+            &Point { x: 70., y: 40. },
+            20.,
+            5,
+            Color::from_rgba8(180, 40, 140, 255),
+        );
+        draw_context.draw_line(
+            &Point { x: 0., y: 100. },
+            &Point { x: 100., y: 100. },
+            6.,
+            Color::from_rgba8(255, 255, 255, 255),
+        );
+
+        draw_context.pop_transform();
+    });
+}
+
+#[test]
+#[should_panic(expected = "without a matching push_transform")]
+fn unbalanced_pop_panics() {
+    draw_pixel(|draw_context| draw_context.pop_transform(), 0, 0);
+}